@@ -0,0 +1,93 @@
+use rand::Rng;
+
+/// Sick-leave rate of one SOT (all SOTs if None) in one month (all months if None).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SickLeaveRate{
+    pub sot: Option<String>,
+    pub month: Option<u32>,
+    pub rate: f64,
+}
+
+/// Chance of a worker to be absent in a tact. The most specific rate wins: SOT and month,
+/// SOT only, month only, then the default rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SickLeave{
+    rates: Vec<SickLeaveRate>,
+    // Rate of SOTs and months without one, no absences if None
+    default_rate: Option<f64>,
+}
+
+impl SickLeave{
+    pub fn new(rates: Vec<SickLeaveRate>, default_rate: Option<f64>) -> Self{
+        Self { rates, default_rate }
+    }
+
+    /// Whether absences of the SOT are sampled, by a rate of its own, of all SOTs or the default rate.
+    /// Other SOTs keep their flat presence factor.
+    pub fn has_rate(&self, sot: &str) -> bool{
+        self.default_rate.is_some() || self.rates.iter().any(|rate| rate.sot.as_deref().is_none_or(|rate_sot| rate_sot == sot))
+    }
+
+    /// Month is 1 to 12, None if tacts are undated. Monthly rates need a calendar.
+    pub fn get_rate(&self, sot: &str, month: Option<u32>) -> f64{
+        let find = |sot: Option<&str>, month: Option<u32>| self.rates.iter()
+            .find(|rate| rate.sot.as_deref() == sot && rate.month == month)
+            .map(|rate| rate.rate);
+        let mut candidates = vec![];
+        if month.is_some(){
+            candidates.push((Some(sot), month));
+        }
+        candidates.push((Some(sot), None));
+        if month.is_some(){
+            candidates.push((None, month));
+        }
+        candidates.push((None, None));
+        candidates.into_iter()
+            .find_map(|(sot, month)| find(sot, month))
+            .or(self.default_rate)
+            .unwrap_or(0.0)
+    }
+
+    /// Draws whether one worker is absent for a tact.
+    pub fn sample(&self, sot: &str, month: Option<u32>, rng: &mut impl Rng) -> bool{
+        let rate = self.get_rate(sot, month).clamp(0.0, 1.0);
+        rng.gen_bool(rate)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn most_specific_rate_wins(){
+        let sick_leave = SickLeave::new(vec![
+            SickLeaveRate { sot: Some(String::from("MECH")), month: Some(2), rate: 0.2 },
+            SickLeaveRate { sot: Some(String::from("MECH")), month: None, rate: 0.1 },
+            SickLeaveRate { sot: None, month: Some(2), rate: 0.15 },
+        ], Some(0.05));
+        assert_eq!(sick_leave.get_rate("MECH", Some(2)), 0.2);
+        assert_eq!(sick_leave.get_rate("MECH", Some(3)), 0.1);
+        assert_eq!(sick_leave.get_rate("MECH", None), 0.1);
+        assert_eq!(sick_leave.get_rate("ELEC", Some(2)), 0.15);
+        assert_eq!(sick_leave.get_rate("ELEC", None), 0.05);
+
+        let only_mech = SickLeave::new(vec![SickLeaveRate { sot: Some(String::from("MECH")), month: None, rate: 0.1 }], None);
+        assert!(only_mech.has_rate("MECH"));
+        assert!(!only_mech.has_rate("ELEC"));
+        assert_eq!(only_mech.get_rate("ELEC", None), 0.0);
+    }
+
+    #[test]
+    fn same_seed_same_absences(){
+        let sick_leave = SickLeave::new(vec![], Some(0.3));
+        let draw = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..100).map(|_| sick_leave.sample("MECH", None, &mut rng)).collect::<Vec<bool>>()
+        };
+        assert_eq!(draw(7), draw(7));
+        let absent = draw(7).iter().filter(|absent| **absent).count();
+        assert!(absent > 10 && absent < 50);
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Dates a tact covers and the part of its nominal hours that can be worked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TactPeriod{
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub capacity: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExceptionKind{
+    Holiday,
+    Shutdown,
+    Reduced,
+}
+
+impl ExceptionKind{
+    pub fn parse(kind: &str) -> Option<Self>{
+        match kind.to_ascii_lowercase().as_str(){
+            "holiday" => Some(ExceptionKind::Holiday),
+            "shutdown" => Some(ExceptionKind::Shutdown),
+            "reduced" => Some(ExceptionKind::Reduced),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarException{
+    pub date: NaiveDate,
+    pub kind: ExceptionKind,
+    // Part of the normal day that is worked, 0 for holidays and shutdowns
+    pub capacity: f64,
+    pub reason: String,
+}
+
+/// Maps tacts to dates. Tacts follow each other on the nominal working days, so a holiday does
+/// not move later tacts but reduces the hours of the tact it falls into.
+#[derive(Debug, Clone)]
+pub struct Calendar{
+    start: NaiveDate,
+    // Working days of the first week from start, the pattern repeats every 7 days
+    first_week: Vec<NaiveDate>,
+    hours_per_day: f64,
+    exceptions: HashMap<NaiveDate, CalendarException>,
+}
+
+impl Calendar{
+    pub fn new(start: NaiveDate, working_days: &[Weekday], hours_per_day: f64) -> Self{
+        let first_week = (0..7)
+            .map(|i| start + Duration::days(i))
+            .filter(|date| working_days.contains(&date.weekday()))
+            .collect();
+        Self { start, first_week, hours_per_day, exceptions: HashMap::new() }
+    }
+
+    /// Monday to Friday for 5 working days, Monday to Saturday for 6, ...
+    pub fn default_working_days(working_days_per_week: f64) -> Vec<Weekday>{
+        let days = (working_days_per_week.round() as u32).clamp(1, 7);
+        (0..days).map(|i| Weekday::try_from(i as u8).expect("Weekday index below 7")).collect()
+    }
+
+    pub fn add_exception(&mut self, exception: CalendarException){
+        self.exceptions.insert(exception.date, exception);
+    }
+
+    pub fn get_start(&self) -> NaiveDate{
+        self.start
+    }
+
+    pub fn get_exceptions(&self) -> Vec<&CalendarException>{
+        let mut exceptions: Vec<&CalendarException> = self.exceptions.values().collect();
+        exceptions.sort_by_key(|exception| exception.date);
+        exceptions
+    }
+
+    /// The date of the n-th nominal working day, counting from 0.
+    pub fn get_working_day(&self, n: usize) -> NaiveDate{
+        if self.first_week.is_empty(){
+            return self.start;
+        }
+        let week = (n / self.first_week.len()) as i64;
+        self.first_week[n % self.first_week.len()] + Duration::days(7 * week)
+    }
+
+    pub fn get_day_capacity(&self, date: NaiveDate) -> f64{
+        match self.exceptions.get(&date){
+            Some(exception) => exception.capacity,
+            None => 1.0,
+        }
+    }
+
+    /// The period of the n-th tact (counting from 0) for a tact time in line hours.
+    pub fn get_tact(&self, n: usize, tact_time: f64) -> TactPeriod{
+        let days = self.get_tact_days(n, tact_time);
+        match (days.first(), days.last()){
+            (Some((start, _)), Some((end, _))) => TactPeriod{
+                start: *start,
+                end: *end,
+                capacity: days.iter().map(|(_, hours)| hours).sum::<f64>() / tact_time,
+            },
+            _ => TactPeriod { start: self.start, end: self.start, capacity: 1.0 },
+        }
+    }
+
+    /// The tact in progress at the start of the first working day on or after the date.
+    pub fn get_tact_of_date(&self, date: NaiveDate, tact_time: f64) -> usize{
+        if tact_time <= 0.0{
+            return 0;
+        }
+        (self.get_hour_of_date(date) / tact_time).floor() as usize
+    }
+
+    /// Line hours from the start until the first working day on or after the date.
+    pub fn get_hour_of_date(&self, date: NaiveDate) -> f64{
+        if date <= self.start || self.first_week.is_empty(){
+            return 0.0;
+        }
+        let week = (date - self.start).num_days() / 7;
+        let same_day_first_week = date - Duration::days(7 * week);
+        let days_before = self.first_week.iter().filter(|day| **day < same_day_first_week).count();
+        let day = week as usize * self.first_week.len() + days_before;
+        day as f64 * self.hours_per_day
+    }
+
+    /// Working days the n-th tact reaches into, with the hours worked on each of them.
+    pub fn get_tact_days(&self, n: usize, tact_time: f64) -> Vec<(NaiveDate, f64)>{
+        if self.hours_per_day <= 0.0 || tact_time <= 0.0{
+            return vec![];
+        }
+        let tact_start = n as f64 * tact_time;
+        let tact_end = tact_start + tact_time;
+        let first_day = (tact_start / self.hours_per_day).floor() as usize;
+        // Last day the tact reaches into, a tact ending exactly at the end of a day stays on that day
+        let last_day = ((tact_end / self.hours_per_day).ceil() as usize).max(first_day + 1) - 1;
+
+        let mut days = vec![];
+        for day in first_day..=last_day{
+            let day_start = day as f64 * self.hours_per_day;
+            let day_end = day_start + self.hours_per_day;
+            let overlap = (tact_end.min(day_end) - tact_start.max(day_start)).max(0.0);
+            let date = self.get_working_day(day);
+            days.push((date, overlap * self.get_day_capacity(date)));
+        }
+        days
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate{
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn working_days_skip_weekends(){
+        // 2026-01-02 is a Friday
+        let calendar = Calendar::new(date(2026, 1, 2), &Calendar::default_working_days(5.0), 14.0);
+        assert_eq!(calendar.get_working_day(0), date(2026, 1, 2));
+        assert_eq!(calendar.get_working_day(1), date(2026, 1, 5));
+        assert_eq!(calendar.get_working_day(5), date(2026, 1, 9));
+    }
+
+    #[test]
+    fn holiday_reduces_tact_capacity(){
+        let mut calendar = Calendar::new(date(2026, 1, 5), &Calendar::default_working_days(5.0), 10.0);
+        // Tacts of 20h cover two days: Monday and Tuesday, Wednesday and Thursday, ...
+        let tact = calendar.get_tact(0, 20.0);
+        assert_eq!((tact.start, tact.end, tact.capacity), (date(2026, 1, 5), date(2026, 1, 6), 1.0));
+
+        calendar.add_exception(CalendarException { date: date(2026, 1, 6), kind: ExceptionKind::Holiday, capacity: 0.0, reason: String::new() });
+        assert_eq!(calendar.get_tact(0, 20.0).capacity, 0.5);
+        assert_eq!(calendar.get_tact(1, 20.0).capacity, 1.0);
+        assert_eq!(calendar.get_tact(2, 20.0).end, date(2026, 1, 12));
+    }
+
+    #[test]
+    fn tact_of_date(){
+        let calendar = Calendar::new(date(2026, 1, 5), &Calendar::default_working_days(5.0), 10.0);
+        assert_eq!(calendar.get_tact_of_date(date(2026, 1, 1), 20.0), 0);
+        assert_eq!(calendar.get_tact_of_date(date(2026, 1, 7), 20.0), 1);
+        // Saturday falls to Monday, the 5th working day
+        assert_eq!(calendar.get_tact_of_date(date(2026, 1, 10), 20.0), 2);
+        assert_eq!(calendar.get_tact(calendar.get_tact_of_date(date(2026, 1, 13), 20.0), 20.0).end, date(2026, 1, 14));
+    }
+}
@@ -0,0 +1,90 @@
+/// Factors that turn the present hours of a SOT into productive hours, capacity is their product.
+/// Every factor has a global value in the settings that sot.csv may override per SOT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CapacityFactors{
+    // Share of the scheduled hours the workers are present, 1 - bc_inefficiency
+    pub presence: f64,
+    // Productive hours per present hour
+    pub cvat: f64,
+    // Technical efficiency of the hangar
+    pub hangar_efficiency: f64,
+    // Share of the shift left after handovers, briefings and the like
+    pub shift_efficiency: f64,
+}
+
+impl CapacityFactors{
+    /// Factors with the given cvat and nothing else taken away.
+    pub fn new(cvat: f64) -> Self{
+        Self { presence: 1.0, cvat, hangar_efficiency: 1.0, shift_efficiency: 1.0 }
+    }
+
+    /// Productive hours per present hour, without the presence.
+    pub fn get_productivity(&self) -> f64{
+        self.cvat * self.hangar_efficiency * self.shift_efficiency
+    }
+
+    /// Productive hours per scheduled hour.
+    pub fn get_rate(&self) -> f64{
+        self.presence * self.get_productivity()
+    }
+}
+
+/// Hours each capacity factor takes away from the scheduled hours of a SOT. Factors are applied
+/// in order, so each one takes its share of what the ones before left. Learning is booked as the
+/// hours spent on the extra workload of early units, once the SOT works on them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapacityWaterfall{
+    // Hours of the employed workers in their shifts
+    pub scheduled: f64,
+    // Flat absence, sick leave and holidays
+    pub presence: f64,
+    // cvat and the efficiency of single workers
+    pub cvat: f64,
+    pub hangar_efficiency: f64,
+    pub shift_efficiency: f64,
+    pub learning: f64,
+}
+
+impl CapacityWaterfall{
+    /// Books a tact from the scheduled hours, the present hours and the present hours
+    /// weighted by each worker's efficiency.
+    pub fn add_tact(&mut self, factors: &CapacityFactors, scheduled: f64, present: f64, weighted: f64){
+        let after_presence = present * factors.presence;
+        let after_cvat = weighted * factors.presence * factors.cvat;
+        let after_hangar = after_cvat * factors.hangar_efficiency;
+        self.scheduled += scheduled;
+        self.presence += scheduled - after_presence;
+        self.cvat += after_presence - after_cvat;
+        self.hangar_efficiency += after_cvat - after_hangar;
+        self.shift_efficiency += after_hangar * (1.0 - factors.shift_efficiency);
+    }
+
+    pub fn add(&mut self, other: &CapacityWaterfall){
+        self.scheduled += other.scheduled;
+        self.presence += other.presence;
+        self.cvat += other.cvat;
+        self.hangar_efficiency += other.hangar_efficiency;
+        self.shift_efficiency += other.shift_efficiency;
+        self.learning += other.learning;
+    }
+
+    /// Hours left after every factor.
+    pub fn get_productive(&self) -> f64{
+        self.scheduled - self.presence - self.cvat - self.hangar_efficiency - self.shift_efficiency - self.learning
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn waterfall_adds_up_to_rate(){
+        let factors = CapacityFactors { presence: 0.9, cvat: 0.8, hangar_efficiency: 0.95, shift_efficiency: 0.9 };
+        let mut waterfall = CapacityWaterfall::default();
+        waterfall.add_tact(&factors, 100.0, 100.0, 100.0);
+        assert!((waterfall.presence - 10.0).abs() < 1e-9);
+        assert!((waterfall.cvat - 18.0).abs() < 1e-9);
+        assert!((waterfall.get_productive() - 100.0 * factors.get_rate()).abs() < 1e-9);
+    }
+}
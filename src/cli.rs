@@ -0,0 +1,400 @@
+use std::path::PathBuf;
+use chrono::NaiveDate;
+use crate::lending::LendingPolicy;
+use crate::dispatch::Dispatch;
+use crate::unfinished::ExitPolicy;
+
+pub const USAGE: &str = "Usage: ppe-demo [COMMAND] [OPTIONS]
+
+Commands:
+  run          Run the simulation and write output_<hangar>.csv, sot_util_<hangar>.csv,
+               worker_hours_<hangar>.csv, capacity_<hangar>.csv, delivery_<hangar>.csv
+               and summary.csv (default),
+               lending_<hangar>.csv with --lending, overtime_<hangar>.csv with overtime.csv,
+               downtime_<hangar>.csv with downtime.csv, events_<hangar>.csv with --events,
+               blocked_<hangar>.csv with task predecessors,
+               comparison_<hangar>.csv with predicted workloads
+  validate     Check the input data and list every problem found
+  sweep        Run the simulation for a range of values of one parameter
+
+Options:
+  -s, --scenario <FILE>      TOML scenario file, options below override its values
+  -d, --data <DIR>           Folder with the input files (default: ./data)
+  -o, --output <DIR>         Folder for the output files (default: data folder)
+      --hangar <NAME>        Simulate only this hangar, can be repeated (default: all hangars)
+  -r, --rate <MSN>           Production rate in MSN per week, sets the tact time from the shifts
+  -t, --tact-time <HOURS>    Tact time in hours, overrides the rate
+  -i, --inefficiency <RATE>  Inefficiency due to BC absence, e.g. 0.086
+      --efficiency <RATE>    Technical efficiency of the hangars, e.g. 0.95 (default: 1)
+      --shift-efficiency <RATE>
+                             Share of the shift left after handovers and briefings (default: 1)
+      --start <DATE>         Date of the first tact, e.g. 2026-01-05, adds entry and exit dates
+      --sick-rate <RATE>     Sample sick leave per worker and tact instead of the inefficiency
+      --seed <N>             Seed for the sick-leave sampling (default: 1)
+      --replications <N>     Runs with consecutive seeds, writes replications.csv
+      --overtime-cost <COST> Cost per worker hour of overtime for rows of overtime.csv without one
+      --lending <POLICY>     Idle workers help other SOTs: off, same-station or adjacent
+      --lending-efficiency <RATE>
+                             Productivity of lent workers (default: 0.8)
+      --dispatch <RULE>      Order in which SOTs work on their tasks: downstream (default),
+                             oldest-osw, largest-task, shortest-task or critical-path
+      --unfinished-work <POLICY>
+                             OSW at line exit: sink (default), rework, delivery-centre or hold
+      --exit-capacity <HOURS>
+                             Productive hours per tact of the rework area or delivery centre
+      --transfer-hours <HOURS>
+                             Hours to move an MSN to the delivery centre (default: 0)
+      --max-held-tacts <TACTS>
+                             Tacts in a row the hold policy may keep an MSN before the run
+                             stops with an error (default: 1000)
+      --learning-rate <RATE> Learning rate per doubling of units of a version, e.g. 0.85
+      --learning-floor <RATE>
+                             Unit time relative to the first unit where learning stops (default: 0.5)
+      --events               Write every event of the run (tacts, shifts, MSN entries and exits,
+                             task completions, absences) to events_<hangar>.csv
+  -f, --format <FORMAT>      Summary format: text, csv or json (default: text)
+  -v, --verbose              Print the simulation log
+  -q, --quiet                Only print errors
+  -h, --help                 Print this help
+
+Sweep options:
+      --param <NAME>         Parameter to sweep: tact-time or inefficiency
+      --values <LIST>        Comma separated values, e.g. 8.5,9,9.333
+      --from <X> --to <Y> --step <Z>
+                             Range of values, alternative to --values
+
+The input folder must contain the following files and columns:
+  sot.csv: sot, bc, cvat, stations
+           (optional shifts column, e.g. early;late, all shifts if empty)
+           (optional absence, technical_efficiency, shift_efficiency, dispatch override the settings)
+  stations.csv: station
+  tactplan.csv: msn, version
+  tasks.csv: station, sot, version, workload
+             (station may list several consecutive stations in line order, e.g. S1;S2)
+             (optional qualifications a worker needs, e.g. NDT;SIGNOFF)
+             (optional id and predecessors, ids of the version's tasks to finish first, e.g. T1;T2)
+             (optional predicted_workload, the planned workload, workload is the actual one)
+Optional:
+  workers.csv: id, sot
+               (optional efficiency, shift, qualifications e.g. NDT;WIRING)
+               Listed workers replace the bc headcount of their SOT.
+  exceptions.csv: date, kind (holiday, shutdown or reduced), used with a start date
+                  (optional end_date, capacity of reduced days e.g. 0.5, reason)
+  sickleave.csv: rate, sot and month (1-12, needs a start date), empty sot or month for all
+                 Turns on sick-leave sampling like --sick-rate for the SOTs it has rates for.
+  learning.csv: rate, optional sot, version and floor, empty sot or version for all
+                Turns on the learning curve like --learning-rate.
+  overtime.csv: sot, max_per_tact (hours per worker), optional max_per_week, threshold, cost
+                (overtime is worked when workload due at the SOT's stations is above threshold)
+  staffing.csv: sot, from_tact or from_date (needs a start date), bc, optional cvat
+                (headcount from that tact on, not for SOTs listed in workers.csv)
+  holidays.csv: worker or sot, start_date, optional end_date and headcount, needs a start date
+                (a sot without headcount takes the whole SOT off)
+  downtime.csv: start_tact or start_date (needs a start date), duration (hours), optional station, reason
+                (no work on the station while it is down, the whole hangar if station is empty)
+Every file may have an additional hangar column. Rows without a hangar apply to all hangars.
+";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command{
+    Run,
+    Validate,
+    Sweep(SweepSpec),
+    Help,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format{
+    Text,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SweepParameter{
+    TactTime,
+    Inefficiency,
+}
+
+impl SweepParameter{
+    pub fn get_name(&self) -> &'static str{
+        match self{
+            SweepParameter::TactTime => "tact-time",
+            SweepParameter::Inefficiency => "inefficiency",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepSpec{
+    pub parameter: SweepParameter,
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options{
+    pub command: Command,
+    pub scenario: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub hangars: Vec<String>,
+    pub production_rate: Option<f64>,
+    pub tact_time: Option<f64>,
+    pub inefficiency: Option<f64>,
+    pub efficiency: Option<f64>,
+    pub shift_efficiency: Option<f64>,
+    pub log_events: bool,
+    pub start_date: Option<NaiveDate>,
+    pub sick_leave_rate: Option<f64>,
+    pub seed: Option<u64>,
+    pub replications: Option<u32>,
+    pub learning_rate: Option<f64>,
+    pub learning_floor: Option<f64>,
+    pub overtime_cost: Option<f64>,
+    pub lending: Option<LendingPolicy>,
+    pub lending_efficiency: Option<f64>,
+    pub dispatch: Option<Dispatch>,
+    pub exit_policy: Option<ExitPolicy>,
+    pub exit_capacity: Option<f64>,
+    pub transfer_hours: Option<f64>,
+    pub max_held_tacts: Option<usize>,
+    pub format: Format,
+    // 0 = quiet, 1 = normal, 2 = verbose
+    pub verbosity: u8,
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, String>{
+    let value = value.ok_or(format!("Missing value for {}", option))?;
+    value.parse::<T>().map_err(|_| format!("Invalid number '{}' for {}", value, option))
+}
+
+fn expand_range(from: f64, to: f64, step: f64) -> Result<Vec<f64>, String>{
+    if step <= 0.0 || to < from{
+        return Err(String::from("Sweep range needs --from <= --to and a positive --step"));
+    }
+    let steps = ((to - from) / step + 1e-9).floor() as usize;
+    Ok((0..=steps).map(|i| from + i as f64 * step).collect())
+}
+
+/// Parses the command line arguments without the program name.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String>{
+    let mut args = args.into_iter().peekable();
+    let mut command = match args.peek().map(|a| a.as_str()){
+        Some("run") => { args.next(); Command::Run }
+        Some("validate") => { args.next(); Command::Validate }
+        Some("sweep") => { args.next(); Command::Sweep(SweepSpec { parameter: SweepParameter::TactTime, values: vec![] }) }
+        _ => Command::Run,
+    };
+
+    let mut options = Options{
+        command: Command::Run,
+        scenario: None,
+        data_dir: None,
+        output_dir: None,
+        hangars: vec![],
+        production_rate: None,
+        tact_time: None,
+        inefficiency: None,
+        efficiency: None,
+        shift_efficiency: None,
+        log_events: false,
+        start_date: None,
+        sick_leave_rate: None,
+        seed: None,
+        replications: None,
+        learning_rate: None,
+        learning_floor: None,
+        overtime_cost: None,
+        lending: None,
+        lending_efficiency: None,
+        dispatch: None,
+        exit_policy: None,
+        exit_capacity: None,
+        transfer_hours: None,
+        max_held_tacts: None,
+        format: Format::Text,
+        verbosity: 1,
+    };
+    let mut sweep_parameter = None;
+    let mut sweep_values = None;
+    let (mut from, mut to, mut step) = (None, None, None);
+
+    while let Some(arg) = args.next(){
+        match arg.as_str(){
+            "-h" | "--help" => command = Command::Help,
+            "-s" | "--scenario" => options.scenario = Some(PathBuf::from(args.next().ok_or("Missing value for --scenario")?)),
+            "-d" | "--data" => options.data_dir = Some(PathBuf::from(args.next().ok_or("Missing value for --data")?)),
+            "-o" | "--output" => options.output_dir = Some(PathBuf::from(args.next().ok_or("Missing value for --output")?)),
+            "--hangar" => options.hangars.push(args.next().ok_or("Missing value for --hangar")?),
+            "-r" | "--rate" => options.production_rate = Some(parse_number(&arg, args.next())?),
+            "-t" | "--tact-time" => options.tact_time = Some(parse_number(&arg, args.next())?),
+            "-i" | "--inefficiency" => options.inefficiency = Some(parse_number(&arg, args.next())?),
+            "--efficiency" => options.efficiency = Some(parse_number(&arg, args.next())?),
+            "--shift-efficiency" => options.shift_efficiency = Some(parse_number(&arg, args.next())?),
+            "--events" => options.log_events = true,
+            "--start" => {
+                let value = args.next().ok_or("Missing value for --start")?;
+                let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| format!("Invalid date '{}' for --start, expected YYYY-MM-DD", value))?;
+                options.start_date = Some(date);
+            }
+            "--sick-rate" => options.sick_leave_rate = Some(parse_number(&arg, args.next())?),
+            "--seed" => options.seed = Some(parse_number(&arg, args.next())?),
+            "--replications" => options.replications = Some(parse_number(&arg, args.next())?),
+            "--learning-rate" => options.learning_rate = Some(parse_number(&arg, args.next())?),
+            "--learning-floor" => options.learning_floor = Some(parse_number(&arg, args.next())?),
+            "--overtime-cost" => options.overtime_cost = Some(parse_number(&arg, args.next())?),
+            "--lending" => {
+                let value = args.next().ok_or("Missing value for --lending")?;
+                let policy = LendingPolicy::parse(&value).ok_or(format!("Unknown lending policy '{}', expected off, same-station or adjacent", value))?;
+                options.lending = Some(policy);
+            }
+            "--lending-efficiency" => options.lending_efficiency = Some(parse_number(&arg, args.next())?),
+            "--dispatch" => {
+                let value = args.next().ok_or("Missing value for --dispatch")?;
+                let rule = Dispatch::parse(&value).ok_or(format!("Unknown dispatch rule '{}', expected downstream, oldest-osw, largest-task, shortest-task or critical-path", value))?;
+                options.dispatch = Some(rule);
+            }
+            "--unfinished-work" => {
+                let value = args.next().ok_or("Missing value for --unfinished-work")?;
+                let policy = ExitPolicy::parse(&value).ok_or(format!("Unknown unfinished work policy '{}', expected sink, rework, delivery-centre or hold", value))?;
+                options.exit_policy = Some(policy);
+            }
+            "--exit-capacity" => options.exit_capacity = Some(parse_number(&arg, args.next())?),
+            "--transfer-hours" => options.transfer_hours = Some(parse_number(&arg, args.next())?),
+            "--max-held-tacts" => options.max_held_tacts = Some(parse_number(&arg, args.next())?),
+            "-f" | "--format" => {
+                options.format = match args.next().as_deref(){
+                    Some("text") => Format::Text,
+                    Some("csv") => Format::Csv,
+                    Some("json") => Format::Json,
+                    other => return Err(format!("Unknown format {:?}, expected text, csv or json", other.unwrap_or(""))),
+                }
+            }
+            "-v" | "--verbose" => options.verbosity = 2,
+            "-q" | "--quiet" => options.verbosity = 0,
+            "--param" => {
+                sweep_parameter = match args.next().as_deref(){
+                    Some("tact-time") => Some(SweepParameter::TactTime),
+                    Some("inefficiency") => Some(SweepParameter::Inefficiency),
+                    other => return Err(format!("Unknown sweep parameter {:?}, expected tact-time or inefficiency", other.unwrap_or(""))),
+                }
+            }
+            "--values" => {
+                let list = args.next().ok_or("Missing value for --values")?;
+                let values = list.split(',')
+                    .map(|v| parse_number("--values", Some(v.trim().to_string())))
+                    .collect::<Result<Vec<f64>, String>>()?;
+                sweep_values = Some(values);
+            }
+            "--from" => from = Some(parse_number(&arg, args.next())?),
+            "--to" => to = Some(parse_number(&arg, args.next())?),
+            "--step" => step = Some(parse_number(&arg, args.next())?),
+            other => return Err(format!("Unknown argument {}", other)),
+        }
+    }
+
+    match &mut command{
+        Command::Sweep(spec) => {
+            spec.parameter = sweep_parameter.ok_or("sweep needs --param tact-time or --param inefficiency")?;
+            spec.values = match (sweep_values, from, to, step){
+                (Some(values), _, _, _) => values,
+                (None, Some(from), Some(to), Some(step)) => expand_range(from, to, step)?,
+                _ => return Err(String::from("sweep needs --values or --from, --to and --step")),
+            };
+        }
+        _ => {
+            if sweep_parameter.is_some() || sweep_values.is_some() || from.is_some() || to.is_some() || step.is_some(){
+                return Err(String::from("--param, --values, --from, --to and --step only apply to sweep"));
+            }
+        }
+    }
+    options.command = command;
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn args(line: &str) -> Vec<String>{
+        line.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn defaults_to_run(){
+        let options = parse_args(args("")).unwrap();
+        assert_eq!(options.command, Command::Run);
+        assert!(options.hangars.is_empty());
+        assert_eq!(options.scenario, None);
+        assert_eq!(options.data_dir, None);
+    }
+
+    #[test]
+    fn parses_run_options(){
+        let options = parse_args(args("run -s case.toml -d in -o out --hangar 8 --hangar 9 -r 7.5 -t 9.333 -i 0.1 --start 2026-01-05 -f csv -q")).unwrap();
+        assert_eq!(options.scenario, Some(PathBuf::from("case.toml")));
+        assert_eq!(options.data_dir, Some(PathBuf::from("in")));
+        assert_eq!(options.output_dir, Some(PathBuf::from("out")));
+        assert_eq!(options.hangars, vec!["8", "9"]);
+        assert_eq!(options.production_rate, Some(7.5));
+        assert_eq!(options.tact_time, Some(9.333));
+        assert_eq!(options.inefficiency, Some(0.1));
+        assert_eq!(options.start_date, NaiveDate::from_ymd_opt(2026, 1, 5));
+        assert_eq!(options.format, Format::Csv);
+        assert_eq!(options.verbosity, 0);
+    }
+
+    #[test]
+    fn parses_sick_leave_options(){
+        let options = parse_args(args("run --sick-rate 0.08 --seed 42 --replications 10")).unwrap();
+        assert_eq!(options.sick_leave_rate, Some(0.08));
+        assert_eq!(options.seed, Some(42));
+        assert_eq!(options.replications, Some(10));
+        assert!(parse_args(args("run --replications 2.5")).is_err());
+    }
+
+    #[test]
+    fn parses_lending_options(){
+        let options = parse_args(args("run --lending same-station --lending-efficiency 0.7")).unwrap();
+        assert_eq!(options.lending, Some(LendingPolicy::SameStation));
+        assert_eq!(options.lending_efficiency, Some(0.7));
+        assert!(parse_args(args("run --lending everywhere")).is_err());
+    }
+
+    #[test]
+    fn parses_dispatch_rule(){
+        assert_eq!(parse_args(args("run --dispatch shortest-task")).unwrap().dispatch, Some(Dispatch::ShortestTask));
+        assert!(parse_args(args("run --dispatch random")).is_err());
+    }
+
+    #[test]
+    fn parses_unfinished_work_options(){
+        let options = parse_args(args("run --unfinished-work delivery-centre --exit-capacity 80 --transfer-hours 14")).unwrap();
+        assert_eq!(options.exit_policy, Some(ExitPolicy::DeliveryCentre));
+        assert_eq!(options.exit_capacity, Some(80.0));
+        assert_eq!(options.transfer_hours, Some(14.0));
+        assert_eq!(parse_args(args("run --unfinished-work hold --max-held-tacts 50")).unwrap().max_held_tacts, Some(50));
+        assert!(parse_args(args("run --unfinished-work later")).is_err());
+    }
+
+    #[test]
+    fn parses_sweep_range(){
+        let options = parse_args(args("sweep --param tact-time --from 8 --to 9 --step 0.5")).unwrap();
+        match options.command{
+            Command::Sweep(spec) => {
+                assert_eq!(spec.parameter, SweepParameter::TactTime);
+                assert_eq!(spec.values, vec![8.0, 8.5, 9.0]);
+            }
+            other => panic!("Expected sweep, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_argument(){
+        assert!(parse_args(args("run --foo")).is_err());
+        assert!(parse_args(args("sweep --values 1,2")).is_err());
+        assert!(parse_args(args("run --param tact-time --values 8,9")).is_err());
+        assert!(parse_args(args("validate --from 8")).is_err());
+        assert!(parse_args(args("run --start 05.01.2026")).is_err());
+    }
+}
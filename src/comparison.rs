@@ -0,0 +1,72 @@
+use indexmap::IndexMap;
+use crate::msn::MSN;
+
+/// Workload of the tasks of an MSN, SOT and station as predicted and as it actually was, with the
+/// OSW the MSN left the line with in a run with either.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadComparison{
+    pub msn: u32,
+    pub sot: String,
+    // Stations of the tasks as in tasks.csv, e.g. S1;S2
+    pub station: String,
+    pub predicted_workload: f64,
+    pub actual_workload: f64,
+    pub predicted_osw: f64,
+    pub actual_osw: f64,
+}
+
+impl WorkloadComparison{
+    /// Predicted minus actual workload, negative if the planning underestimated the work.
+    pub fn get_workload_error(&self) -> f64{
+        self.predicted_workload - self.actual_workload
+    }
+}
+
+/// Compares the tasks of the tact plan per MSN, SOT and station. Task workloads at entry (after
+/// learning) and task OSW of both runs are in tact plan order, as MSNs leave the line in the order
+/// they entered.
+pub fn compare(msns: &[MSN], predicted_workload: &[Vec<f64>], predicted_osw: &[Vec<f64>], actual_workload: &[Vec<f64>], actual_osw: &[Vec<f64>])
+    -> Vec<WorkloadComparison>{
+    let value = |values: &[Vec<f64>], k: usize, i: usize| values.get(k).and_then(|tasks| tasks.get(i)).copied().unwrap_or(0.0);
+    // Keyed by position in the tact plan, an MSN may be planned twice
+    let mut rows: IndexMap<(usize, &str, String), WorkloadComparison> = IndexMap::new();
+    for (k, msn) in msns.iter().enumerate(){
+        for (i, task) in msn.get_all_tasks().iter().enumerate(){
+            let station = task.get_station_refs().join(";");
+            let row = rows.entry((k, task.get_sot(), station.clone())).or_insert_with(|| WorkloadComparison {
+                msn: msn.get_msn(),
+                sot: task.get_sot().to_owned(),
+                station,
+                predicted_workload: 0.0,
+                actual_workload: 0.0,
+                predicted_osw: 0.0,
+                actual_osw: 0.0,
+            });
+            row.predicted_workload += value(predicted_workload, k, i);
+            row.actual_workload += value(actual_workload, k, i);
+            row.predicted_osw += value(predicted_osw, k, i);
+            row.actual_osw += value(actual_osw, k, i);
+        }
+    }
+    rows.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::task::Task;
+
+    #[test]
+    fn sums_tasks_per_msn_sot_and_station(){
+        let task = |workload: f64, station: &str, sot: &str| Task::new(workload, vec![String::from(station)], String::from(sot), String::from("A"));
+        let tasks = vec![task(10.0, "S1", "MECH"), task(5.0, "S1", "MECH"), task(4.0, "S2", "ELEC")];
+        let msns = vec![MSN::new(1, "A", &tasks)];
+        let rows = compare(&msns, &[vec![8.0, 5.0, 6.0]], &[vec![0.0, 0.0, 1.0]], &[vec![10.0, 5.0, 4.0]], &[vec![2.0, 0.0, 0.0]]);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].predicted_workload, rows[0].actual_workload), (13.0, 15.0));
+        assert_eq!(rows[0].get_workload_error(), -2.0);
+        assert_eq!((rows[0].predicted_osw, rows[0].actual_osw), (0.0, 2.0));
+        assert_eq!((rows[1].station.as_str(), rows[1].predicted_osw), ("S2", 1.0));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use crate::error::PpeError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputFile{
+    Tasks,
+    TactPlan,
+    Sot,
+    Stations,
+    Exceptions,
+    Workers,
+    SickLeave,
+    Holidays,
+    Learning,
+    Overtime,
+    Staffing,
+    Downtime,
+}
+
+impl InputFile{
+    pub fn file_name(&self) -> &'static str{
+        match self{
+            InputFile::Tasks => "tasks.csv",
+            InputFile::TactPlan => "tactplan.csv",
+            InputFile::Sot => "sot.csv",
+            InputFile::Stations => "stations.csv",
+            InputFile::Exceptions => "exceptions.csv",
+            InputFile::Workers => "workers.csv",
+            InputFile::SickLeave => "sickleave.csv",
+            InputFile::Holidays => "holidays.csv",
+            InputFile::Learning => "learning.csv",
+            InputFile::Overtime => "overtime.csv",
+            InputFile::Staffing => "staffing.csv",
+            InputFile::Downtime => "downtime.csv",
+        }
+    }
+}
+
+/// Where the simulation reads its input tables from.
+#[derive(Debug, Clone)]
+pub enum DataSource{
+    // All files with their default names in one folder
+    Directory(PathBuf),
+    // Every file with its own path
+    Files(HashMap<InputFile, PathBuf>),
+    // File contents held in memory (tests, embedding in other tools)
+    Memory(HashMap<InputFile, String>),
+}
+
+impl DataSource{
+    pub fn directory(path: impl AsRef<Path>) -> Self{
+        DataSource::Directory(path.as_ref().to_path_buf())
+    }
+
+    pub fn files() -> Self{
+        DataSource::Files(HashMap::new())
+    }
+
+    pub fn memory() -> Self{
+        DataSource::Memory(HashMap::new())
+    }
+
+    /// Sets the path of one file. Only has an effect on DataSource::Files.
+    pub fn with_file(mut self, file: InputFile, path: impl AsRef<Path>) -> Self{
+        if let DataSource::Files(files) = &mut self{
+            files.insert(file, path.as_ref().to_path_buf());
+        }
+        self
+    }
+
+    /// Sets the contents of one file. Only has an effect on DataSource::Memory.
+    pub fn with_data(mut self, file: InputFile, data: &str) -> Self{
+        if let DataSource::Memory(files) = &mut self{
+            files.insert(file, String::from(data));
+        }
+        self
+    }
+
+    /// Name of the file as used in messages
+    pub fn describe(&self, file: InputFile) -> String{
+        match self{
+            DataSource::Directory(dir) => dir.join(file.file_name()).display().to_string(),
+            DataSource::Files(files) => match files.get(&file){
+                Some(path) => path.display().to_string(),
+                None => String::from(file.file_name()),
+            },
+            DataSource::Memory(_) => format!("<memory>/{}", file.file_name()),
+        }
+    }
+
+    pub fn open(&self, file: InputFile) -> Result<Box<dyn Read + '_>, PpeError>{
+        self.open_optional(file)?.ok_or_else(|| not_found(&self.describe(file)))
+    }
+
+    /// Opens an input that may be left out. Returns None if the file is not configured or, in a
+    /// directory, does not exist. A configured file that can't be opened is an error.
+    pub fn open_optional(&self, file: InputFile) -> Result<Option<Box<dyn Read + '_>>, PpeError>{
+        let name = self.describe(file);
+        match self{
+            DataSource::Directory(dir) => match File::open(dir.join(file.file_name())){
+                Ok(f) => Ok(Some(Box::new(f))),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(PpeError::missing_file(&name, e)),
+            },
+            DataSource::Files(files) => match files.get(&file){
+                Some(path) => {
+                    let f = File::open(path).map_err(|e| PpeError::missing_file(&name, e))?;
+                    Ok(Some(Box::new(f)))
+                }
+                None => Ok(None),
+            },
+            DataSource::Memory(files) => Ok(files.get(&file).map(|data| Box::new(data.as_bytes()) as Box<dyn Read>)),
+        }
+    }
+}
+
+fn not_found(name: &str) -> PpeError{
+    PpeError::missing_file(name, std::io::Error::new(std::io::ErrorKind::NotFound, "no such input configured"))
+}
+
+/// Where the simulation writes its result tables to.
+#[derive(Debug, Clone)]
+pub enum OutputSink{
+    Directory(PathBuf),
+    Memory(HashMap<String, Vec<u8>>),
+}
+
+impl OutputSink{
+    pub fn directory(path: impl AsRef<Path>) -> Self{
+        OutputSink::Directory(path.as_ref().to_path_buf())
+    }
+
+    pub fn memory() -> Self{
+        OutputSink::Memory(HashMap::new())
+    }
+
+    pub fn write(&mut self, file_name: &str, data: Vec<u8>) -> Result<(), PpeError>{
+        match self{
+            OutputSink::Directory(dir) => {
+                std::fs::create_dir_all(&dir).map_err(|e| PpeError::output(&dir.display().to_string(), e))?;
+                let path = dir.join(file_name);
+                std::fs::write(&path, data).map_err(|e| PpeError::output(&path.display().to_string(), e))
+            }
+            OutputSink::Memory(files) => {
+                files.insert(String::from(file_name), data);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns a file written to a memory sink
+    pub fn get(&self, file_name: &str) -> Option<&str>{
+        match self{
+            OutputSink::Memory(files) => files.get(file_name).and_then(|data| std::str::from_utf8(data).ok()),
+            OutputSink::Directory(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub fn fixture() -> DataSource{
+    DataSource::memory()
+        .with_data(InputFile::Stations, "station\nS1\nS2\nS3\nS4\n")
+        .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,4,0.8,S1;S2\nELEC,2,0.9,S3;S4\n")
+        .with_data(InputFile::Tasks, "station,sot,version,workload\n\
+            S1,MECH,A,40\n\
+            S2,MECH,A,30\n\
+            S3,ELEC,A,20\n\
+            S4,ELEC,A,10\n\
+            S1,MECH,B,50\n\
+            S3,ELEC,B,25\n")
+        .with_data(InputFile::TactPlan, "msn,version\n1,A\n2,B\n3,A\n")
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn memory_source_reads_back_data(){
+        let source = fixture();
+        let mut data = String::new();
+        source.open(InputFile::TactPlan).unwrap().read_to_string(&mut data).unwrap();
+        assert!(data.starts_with("msn,version"));
+    }
+
+    #[test]
+    fn missing_memory_file_is_an_error(){
+        let source = DataSource::memory();
+        assert!(matches!(source.open(InputFile::Tasks), Err(PpeError::MissingFile { .. })));
+    }
+
+    #[test]
+    fn only_unconfigured_inputs_are_optional(){
+        let dir = std::env::temp_dir().join(format!("ppe-optional-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(DataSource::directory(&dir).open_optional(InputFile::Workers).unwrap().is_none());
+        assert!(DataSource::files().open_optional(InputFile::Workers).unwrap().is_none());
+        // A path given for the file has to be there
+        let configured = DataSource::files().with_file(InputFile::Workers, dir.join("workers.csv"));
+        assert!(matches!(configured.open_optional(InputFile::Workers), Err(PpeError::MissingFile { .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn output_directory_is_created(){
+        let dir = std::env::temp_dir().join(format!("ppe-output-{}", std::process::id()));
+        let mut sink = OutputSink::directory(dir.join("out"));
+        sink.write("summary.csv", b"hangar\n".to_vec()).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("out").join("summary.csv")).unwrap(), "hangar\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+/// A task a SOT can work on in the current tact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job{
+    // Station the MSN is at, higher ids are further down the line
+    pub station_id: usize,
+    // Position of the task in its MSN
+    pub position: usize,
+    // Tact in which the MSN entered the hangar
+    pub entry_tact: usize,
+    pub workload: f64,
+    // Workload of the task and its longest chain of successors
+    pub critical_path: f64,
+    // The task is listed at the MSN's station, otherwise it is open work from an earlier station
+    pub at_station: bool,
+    // The task can't be continued at a later station
+    pub ends_here: bool,
+    // Position of the MSN's station in the SOT's station assignment, None if not assigned to the SOT
+    pub assignment: Option<usize>,
+}
+
+/// Tasks of the MSN at a station, worked on in order with that station's downtime budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch{
+    pub station_id: usize,
+    pub positions: Vec<usize>,
+}
+
+/// Decides in which order a SOT works on the tasks it can reach. The jobs are its unfinished tasks
+/// that don't wait for predecessors, per station from the end of the line and in task order.
+/// The SOT works through the batches in the returned order until its time is up; a task may be in
+/// several batches, but only once per batch.
+pub trait DispatchRule: fmt::Debug + Send + Sync{
+    fn get_name(&self) -> &'static str;
+
+    fn order(&self, jobs: Vec<Job>) -> Vec<Batch>;
+}
+
+/// Groups jobs in order into batches, a new batch starts whenever the station changes.
+pub fn into_batches(jobs: impl IntoIterator<Item = Job>) -> Vec<Batch>{
+    let mut batches: Vec<Batch> = vec![];
+    for job in jobs{
+        match batches.last_mut(){
+            Some(batch) if batch.station_id == job.station_id => batch.positions.push(job.position),
+            _ => batches.push(Batch { station_id: job.station_id, positions: vec![job.position] }),
+        }
+    }
+    batches
+}
+
+/// Most downstream MSN first: the tasks at every station from the end of the line, tasks that can't
+/// travel on first, then open work of the MSNs at the SOT's own stations in assignment order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Downstream;
+
+impl DispatchRule for Downstream{
+    fn get_name(&self) -> &'static str{
+        "downstream"
+    }
+
+    fn order(&self, jobs: Vec<Job>) -> Vec<Batch>{
+        let mut at_station: Vec<&Job> = jobs.iter().filter(|job| job.at_station).collect();
+        // Stable, so stations stay in line order and tasks in task order
+        at_station.sort_by_key(|job| (std::cmp::Reverse(job.station_id), !job.ends_here));
+        let mut batches = into_batches(at_station.into_iter().cloned());
+
+        let mut assigned: Vec<&Job> = jobs.iter().filter(|job| job.assignment.is_some()).collect();
+        assigned.sort_by_key(|job| job.assignment);
+        batches.extend(into_batches(assigned.into_iter().cloned()));
+        batches
+    }
+}
+
+/// Open work from earlier stations first, oldest MSN first, then as Downstream.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OldestOsw;
+
+impl DispatchRule for OldestOsw{
+    fn get_name(&self) -> &'static str{
+        "oldest_osw"
+    }
+
+    fn order(&self, jobs: Vec<Job>) -> Vec<Batch>{
+        let mut open_work: Vec<Job> = jobs.iter().filter(|job| !job.at_station).cloned().collect();
+        open_work.sort_by_key(|job| job.entry_tact);
+        let mut batches = into_batches(open_work);
+        batches.extend(Downstream.order(jobs));
+        batches
+    }
+}
+
+/// The task with the most workload left first, the most downstream first among equal ones.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LargestTask;
+
+impl DispatchRule for LargestTask{
+    fn get_name(&self) -> &'static str{
+        "largest_task"
+    }
+
+    fn order(&self, mut jobs: Vec<Job>) -> Vec<Batch>{
+        jobs.sort_by(|a, b| b.workload.total_cmp(&a.workload));
+        into_batches(jobs)
+    }
+}
+
+/// The task with the least workload left first, to finish as many tasks as possible.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShortestTask;
+
+impl DispatchRule for ShortestTask{
+    fn get_name(&self) -> &'static str{
+        "shortest_task"
+    }
+
+    fn order(&self, mut jobs: Vec<Job>) -> Vec<Batch>{
+        jobs.sort_by(|a, b| a.workload.total_cmp(&b.workload));
+        into_batches(jobs)
+    }
+}
+
+/// The task with the longest chain of successor workload first, largest task first without
+/// task precedence.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CriticalPath;
+
+impl DispatchRule for CriticalPath{
+    fn get_name(&self) -> &'static str{
+        "critical_path"
+    }
+
+    fn order(&self, mut jobs: Vec<Job>) -> Vec<Batch>{
+        jobs.sort_by(|a, b| b.critical_path.total_cmp(&a.critical_path));
+        into_batches(jobs)
+    }
+}
+
+/// The built-in dispatch rules, as set in the scenario and sot.csv.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dispatch{
+    #[default]
+    Downstream,
+    OldestOsw,
+    LargestTask,
+    ShortestTask,
+    CriticalPath,
+}
+
+impl Dispatch{
+    pub fn parse(rule: &str) -> Option<Self>{
+        match rule.trim().replace('-', "_").as_str(){
+            "downstream" => Some(Dispatch::Downstream),
+            "oldest_osw" => Some(Dispatch::OldestOsw),
+            "largest_task" => Some(Dispatch::LargestTask),
+            "shortest_task" => Some(Dispatch::ShortestTask),
+            "critical_path" => Some(Dispatch::CriticalPath),
+            _ => None,
+        }
+    }
+
+    pub fn get_rule(&self) -> Arc<dyn DispatchRule>{
+        match self{
+            Dispatch::Downstream => Arc::new(Downstream),
+            Dispatch::OldestOsw => Arc::new(OldestOsw),
+            Dispatch::LargestTask => Arc::new(LargestTask),
+            Dispatch::ShortestTask => Arc::new(ShortestTask),
+            Dispatch::CriticalPath => Arc::new(CriticalPath),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn job(station_id: usize, position: usize, workload: f64, at_station: bool, assignment: Option<usize>) -> Job{
+        Job { station_id, position, entry_tact: 3 - station_id, workload, critical_path: workload, at_station, ends_here: true, assignment }
+    }
+
+    #[test]
+    fn rules_order_jobs(){
+        // MSN at S2 (assigned to the SOT) with a task of S2 and open work, MSN at S1 with one task
+        let jobs = vec![job(2, 0, 5.0, true, Some(0)), job(2, 1, 20.0, false, Some(0)), job(1, 0, 10.0, true, None)];
+        let positions = |batches: Vec<Batch>| -> Vec<(usize, Vec<usize>)> {
+            batches.into_iter().map(|batch| (batch.station_id, batch.positions)).collect()
+        };
+        assert_eq!(positions(Downstream.order(jobs.clone())), vec![(2, vec![0]), (1, vec![0]), (2, vec![0, 1])]);
+        assert_eq!(positions(OldestOsw.order(jobs.clone()))[0], (2, vec![1]));
+        assert_eq!(positions(LargestTask.order(jobs.clone())), vec![(2, vec![1]), (1, vec![0]), (2, vec![0])]);
+        assert_eq!(positions(ShortestTask.order(jobs.clone())), vec![(2, vec![0]), (1, vec![0]), (2, vec![1])]);
+        assert_eq!(Dispatch::parse("critical-path"), Some(Dispatch::CriticalPath));
+        assert_eq!(Dispatch::parse("random"), None);
+    }
+}
@@ -0,0 +1,34 @@
+/// A period in which a station, or the whole hangar if no station is given, can't be worked on,
+/// from downtime.csv. Start and duration are in line hours from the start of the simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Downtime{
+    pub station: Option<String>,
+    pub start: f64,
+    pub duration: f64,
+    pub reason: String,
+}
+
+impl Downtime{
+    pub fn affects(&self, station: &str) -> bool{
+        self.station.as_deref().is_none_or(|s| s == station)
+    }
+
+    /// Hours of the downtime within the given line hours.
+    pub fn get_overlap(&self, from: f64, to: f64) -> f64{
+        ((self.start + self.duration).min(to) - self.start.max(from)).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn overlap_with_tacts(){
+        let downtime = Downtime { station: Some(String::from("S2")), start: 8.0, duration: 6.0, reason: String::from("crane") };
+        assert_eq!(downtime.get_overlap(0.0, 10.0), 2.0);
+        assert_eq!(downtime.get_overlap(10.0, 20.0), 4.0);
+        assert_eq!(downtime.get_overlap(20.0, 30.0), 0.0);
+        assert!(downtime.affects("S2") && !downtime.affects("S1"));
+    }
+}
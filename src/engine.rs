@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// What happens at an event. MSNs are identified by their number, workers by their id.
+/// A downtime without a station stops the whole hangar.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind{
+    TactStart(usize),
+    TactEnd(usize),
+    ShiftStart(String),
+    ShiftEnd(String),
+    MsnEntry(u32),
+    MsnExit(u32),
+    TaskCompletion { msn: u32, sot: String },
+    Absence { worker: String },
+    DowntimeStart { station: Option<String>, reason: String },
+    DowntimeEnd { station: Option<String>, reason: String },
+}
+
+impl EventKind{
+    pub fn get_name(&self) -> &'static str{
+        match self{
+            EventKind::TactStart(_) => "tact_start",
+            EventKind::TactEnd(_) => "tact_end",
+            EventKind::ShiftStart(_) => "shift_start",
+            EventKind::ShiftEnd(_) => "shift_end",
+            EventKind::MsnEntry(_) => "msn_entry",
+            EventKind::MsnExit(_) => "msn_exit",
+            EventKind::TaskCompletion { .. } => "task_completion",
+            EventKind::Absence { .. } => "absence",
+            EventKind::DowntimeStart { .. } => "downtime_start",
+            EventKind::DowntimeEnd { .. } => "downtime_end",
+        }
+    }
+
+    pub fn get_detail(&self) -> String{
+        match self{
+            EventKind::TactStart(tact) | EventKind::TactEnd(tact) => tact.to_string(),
+            EventKind::ShiftStart(shift) | EventKind::ShiftEnd(shift) => shift.to_owned(),
+            EventKind::MsnEntry(msn) | EventKind::MsnExit(msn) => msn.to_string(),
+            EventKind::TaskCompletion { msn, sot } => format!("{} {}", msn, sot),
+            EventKind::Absence { worker } => worker.to_owned(),
+            EventKind::DowntimeStart { station, reason } | EventKind::DowntimeEnd { station, reason } => {
+                format!("{} {}", station.as_deref().unwrap_or("hangar"), reason).trim_end().to_owned()
+            }
+        }
+    }
+
+    /// Order of events at the same time: a tact ends, with what it finished, before the next one starts.
+    fn get_rank(&self) -> u8{
+        match self{
+            EventKind::TactEnd(_) => 0,
+            EventKind::TaskCompletion { .. } => 1,
+            EventKind::MsnExit(_) => 2,
+            EventKind::ShiftEnd(_) => 3,
+            EventKind::DowntimeEnd { .. } => 4,
+            EventKind::ShiftStart(_) => 5,
+            EventKind::DowntimeStart { .. } => 6,
+            EventKind::MsnEntry(_) => 7,
+            EventKind::TactStart(_) => 8,
+            EventKind::Absence { .. } => 9,
+        }
+    }
+}
+
+/// An event at a time in line hours from the start of the simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event{
+    pub time: f64,
+    pub kind: EventKind,
+    // Events of the same time and rank happen in the order they were scheduled
+    sequence: u64,
+}
+
+impl Eq for Event{}
+
+impl Ord for Event{
+    // BinaryHeap pops the greatest event, so the earliest has to compare greatest
+    fn cmp(&self, other: &Self) -> Ordering{
+        other.time.total_cmp(&self.time)
+            .then_with(|| other.kind.get_rank().cmp(&self.kind.get_rank()))
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Event{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>{
+        Some(self.cmp(other))
+    }
+}
+
+/// Events waiting to happen, earliest first.
+#[derive(Debug, Default)]
+pub struct EventQueue{
+    events: BinaryHeap<Event>,
+    sequence: u64,
+    now: f64,
+}
+
+impl EventQueue{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// Schedules an event, events in the past happen now.
+    pub fn schedule(&mut self, time: f64, kind: EventKind){
+        self.events.push(Event { time: time.max(self.now), kind, sequence: self.sequence });
+        self.sequence += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<Event>{
+        let event = self.events.pop()?;
+        self.now = event.time;
+        Some(event)
+    }
+
+    /// Time of the last event that happened.
+    pub fn get_now(&self) -> f64{
+        self.now
+    }
+
+    /// Time of the next event, None if no event is left.
+    pub fn peek_time(&self) -> Option<f64>{
+        self.events.peek().map(|event| event.time)
+    }
+
+    pub fn len(&self) -> usize{
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.events.is_empty()
+    }
+}
+
+/// Rules that react to events, e.g. the tact schedule of a hangar.
+pub trait Schedule{
+    /// Schedules the events the run starts with.
+    fn start(&mut self, queue: &mut EventQueue);
+
+    /// Handles an event, which may schedule further events.
+    fn handle(&mut self, event: &Event, queue: &mut EventQueue);
+}
+
+/// Event engine: pops events in time order and hands them to the schedule until none are left.
+/// What an event changes is up to the schedule, see TactSchedule for the tact line.
+#[derive(Debug, Default)]
+pub struct Engine{
+    queue: EventQueue,
+    // Events in the order they happened, only kept if asked for
+    log: Option<Vec<Event>>,
+}
+
+impl Engine{
+    pub fn new(keep_log: bool) -> Self{
+        Self { queue: EventQueue::new(), log: keep_log.then(Vec::new) }
+    }
+
+    pub fn run(&mut self, schedule: &mut impl Schedule){
+        schedule.start(&mut self.queue);
+        while let Some(event) = self.queue.pop(){
+            schedule.handle(&event, &mut self.queue);
+            if let Some(log) = self.log.as_mut(){
+                log.push(event);
+            }
+        }
+    }
+
+    pub fn get_log(&self) -> Option<&Vec<Event>>{
+        self.log.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn events_in_time_then_rank_order(){
+        let mut queue = EventQueue::new();
+        queue.schedule(10.0, EventKind::TactStart(1));
+        queue.schedule(10.0, EventKind::MsnEntry(2));
+        queue.schedule(10.0, EventKind::TactEnd(0));
+        queue.schedule(0.0, EventKind::TactStart(0));
+        let order: Vec<&str> = std::iter::from_fn(|| queue.pop()).map(|event| event.kind.get_name()).collect();
+        assert_eq!(order, vec!["tact_start", "tact_end", "msn_entry", "tact_start"]);
+
+        // The past is now
+        queue.schedule(5.0, EventKind::MsnExit(1));
+        assert_eq!(queue.pop().unwrap().time, 10.0);
+    }
+
+    struct Countdown(u32);
+
+    impl Schedule for Countdown{
+        fn start(&mut self, queue: &mut EventQueue){
+            queue.schedule(0.0, EventKind::TactStart(0));
+        }
+
+        fn handle(&mut self, event: &Event, queue: &mut EventQueue){
+            if let EventKind::TactStart(tact) = event.kind{
+                if self.0 > 0{
+                    self.0 -= 1;
+                    queue.schedule(event.time + 1.0, EventKind::TactStart(tact + 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn engine_runs_until_queue_is_empty(){
+        let mut engine = Engine::new(true);
+        engine.run(&mut Countdown(3));
+        let log = engine.get_log().unwrap();
+        assert_eq!(log.len(), 4);
+        assert_eq!(log[3].time, 3.0);
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+use csv::StringRecord;
+
+#[derive(Debug)]
+pub enum PpeError{
+    // Input file could not be opened (or output file could not be created)
+    MissingFile{
+        file: String,
+        source: std::io::Error,
+    },
+    // A row could not be read or parsed. Line is 1-based and counts the header.
+    BadRow{
+        file: String,
+        line: Option<u64>,
+        column: Option<String>,
+        message: String,
+    },
+    // A station is referenced that is not listed in stations.csv
+    UnknownStation{
+        station: String,
+        context: String,
+    },
+    // A SOT is referenced that is not listed in sot.csv
+    UnknownSot{
+        sot: String,
+        context: String,
+    },
+    // A shift is referenced that is not part of the shift system
+    UnknownShift{
+        shift: String,
+        context: String,
+    },
+    // A worker is referenced that is neither in workers.csv nor one of a SOT's bc headcount
+    UnknownWorker{
+        worker: String,
+        context: String,
+    },
+    // A hangar was requested that has no stations in stations.csv
+    UnknownHangar{
+        hangar: String,
+    },
+    // The scenario file could not be parsed
+    BadSettings{
+        file: String,
+        message: String,
+    },
+    // An MSN was held at the last station for more tacts in a row than allowed
+    HoldLimit{
+        hangar: String,
+        msn: u32,
+        tacts: usize,
+    },
+    // Writing results failed
+    Output{
+        file: String,
+        message: String,
+    },
+}
+
+impl PpeError{
+    pub fn missing_file(file: &str, source: std::io::Error) -> Self{
+        PpeError::MissingFile { file: String::from(file), source }
+    }
+
+    /// Translates a csv error into a BadRow, resolving the column name from the headers if possible.
+    pub fn bad_row(file: &str, err: csv::Error, headers: Option<&StringRecord>) -> Self{
+        let line = err.position().map(|pos| pos.line());
+        let column = match err.kind(){
+            csv::ErrorKind::Deserialize { err: de_err, .. } => {
+                de_err.field().map(|index| {
+                    headers
+                        .and_then(|h| h.get(index as usize))
+                        .map(|name| name.to_string())
+                        .unwrap_or(index.to_string())
+                })
+            }
+            _ => None,
+        };
+        let message = match err.kind(){
+            csv::ErrorKind::Deserialize { err: de_err, .. } => de_err.to_string(),
+            _ => err.to_string(),
+        };
+        PpeError::BadRow { file: String::from(file), line, column, message }
+    }
+
+    pub fn output(file: &str, err: impl fmt::Display) -> Self{
+        PpeError::Output { file: String::from(file), message: err.to_string() }
+    }
+}
+
+impl fmt::Display for PpeError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        match self{
+            PpeError::MissingFile { file, source } => {
+                write!(f, "Failed to open file {}: {}", file, source)
+            }
+            PpeError::BadRow { file, line, column, message } => {
+                write!(f, "Failed to read {}", file)?;
+                if let Some(line) = line{
+                    write!(f, " at line {}", line)?;
+                }
+                if let Some(column) = column{
+                    write!(f, ", column '{}'", column)?;
+                }
+                write!(f, ": {}", message)
+            }
+            PpeError::UnknownStation { station, context } => {
+                write!(f, "Station {} not in stations.csv list ({})", station, context)
+            }
+            PpeError::UnknownSot { sot, context } => {
+                write!(f, "SOT {} not in sot.csv list ({})", sot, context)
+            }
+            PpeError::UnknownShift { shift, context } => {
+                write!(f, "Shift {} not in the shift system of the settings ({})", shift, context)
+            }
+            PpeError::UnknownWorker { worker, context } => {
+                write!(f, "Worker {} not in workers.csv list ({})", worker, context)
+            }
+            PpeError::UnknownHangar { hangar } => {
+                write!(f, "Hangar {} has no stations in stations.csv", hangar)
+            }
+            PpeError::BadSettings { file, message } => {
+                write!(f, "Failed to read settings {}: {}", file, message)
+            }
+            PpeError::HoldLimit { hangar, msn, tacts } => {
+                write!(f, "MSN {} was held at the last station of hangar {} for more than {} tacts, the line stopped", msn, hangar, tacts)
+            }
+            PpeError::Output { file, message } => {
+                write!(f, "Failed to write {}: {}", file, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PpeError{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>{
+        match self{
+            PpeError::MissingFile { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn bad_row_resolves_column_name(){
+        let data = "station,sot,version,workload\nS1,SOT1,V1,abc\n";
+        let mut rdr = csv::Reader::from_reader(data.as_bytes());
+        let headers = rdr.headers().unwrap().clone();
+        let err = rdr.deserialize::<(String, String, String, f64)>().next().unwrap().unwrap_err();
+
+        match PpeError::bad_row("tasks.csv", err, Some(&headers)){
+            PpeError::BadRow { line, column, .. } => {
+                assert_eq!(line, Some(2));
+                assert_eq!(column.as_deref(), Some("workload"));
+            }
+            other => panic!("Unexpected error: {}", other),
+        }
+    }
+}
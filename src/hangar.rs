@@ -1,14 +1,102 @@
 use crate::{msn::MSN, sot::SOT, station::Station, task::Task};
+use crate::intern::Interner;
+use crate::calendar::Calendar;
+use crate::absence::SickLeave;
+use crate::holiday::HolidayPlan;
+use crate::lending::Lending;
+use crate::learning::Learning;
+use crate::shift::ShiftSystem;
+use crate::downtime::Downtime;
+use crate::capacity::CapacityWaterfall;
+use crate::dispatch::Job;
+use crate::unfinished::{Delivery, ExitArea, ExitPolicy, UnfinishedWork};
+use std::collections::HashMap;
+use chrono::{Datelike, NaiveDate};
+use rand::{rngs::StdRng, SeedableRng};
 use std::fmt;
 use indexmap::IndexMap;
 
-#[derive(Debug)]
+/// A task finished in a slice of a tact, with the share of the slice that had passed when its
+/// workload was done.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion{
+    pub msn: u32,
+    pub sot: String,
+    pub share: f64,
+}
+
+/// The MSN that left the hangar at the end of a tact. If the MSN at the last station is held,
+/// no MSN moves and the line stops.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TactOutcome{
+    pub exited: Option<u32>,
+    pub held: bool,
+    // Tasks finished in overtime at the end of the tact
+    pub completed: Vec<Completion>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Hangar{
     hangar: String,
     stations: Vec<Station>,
     tact_time: f64,
     sot: Vec<SOT>,
     osw: Vec<f64>,
+    // Station and SOT ids are their positions in stations and sot
+    station_ids: Interner,
+    sot_ids: Interner,
+    // Station ids of every SOT's station assignment, None if not in this hangar
+    sot_station_ids: Vec<Vec<Option<usize>>>,
+    // Maps tacts to dates and available hours, tacts are undated without one
+    calendar: Option<Calendar>,
+    tact_count: usize,
+    // Entry and exit tact of every MSN that left the hangar, same order as osw
+    msn_tacts: Vec<(usize, usize)>,
+    // Learning factor of every MSN that left the hangar, same order as osw
+    msn_learning: Vec<f64>,
+    // Workload left of every task of every MSN that left the hangar, same order as osw
+    task_osw: Vec<Vec<f64>>,
+    // Workload of every task of every MSN when it entered the hangar, after learning, same order as osw
+    task_workload: Vec<Vec<f64>>,
+    // Without a learning curve every MSN needs the workload of tasks.csv
+    learning: Option<Learning>,
+    // MSNs of each version that entered the hangar so far
+    version_units: HashMap<String, u32>,
+    // Without a sick-leave model all workers are present in every tact
+    sick_leave: Option<SickLeave>,
+    rng: StdRng,
+    // Only applied with a calendar, as holidays are planned by date
+    holidays: HolidayPlan,
+    // Idle workers help other SOTs after all SOTs worked on their own tasks
+    lending: Lending,
+    // Weeks for the overtime limits are calendar weeks with a calendar, otherwise line hours
+    hours_per_week: f64,
+    overtime_week: Option<usize>,
+    downtime: Vec<Downtime>,
+    // Part of the current tact every station is not down, same order as stations
+    open_shares: Vec<f64>,
+    // Downtimes every station is in right now, same order as stations
+    stations_down: Vec<usize>,
+    // Downtime hours per tact, by reason
+    downtime_hours: IndexMap<String, Vec<f64>>,
+    // Part of the tact's nominal hours that can be worked, see get_tact_capacity
+    tact_capacity: f64,
+    // Shifts that are on right now, None without a shift timetable, then every SOT works its
+    // average share of every hour
+    shifts: Option<Vec<String>>,
+    // Whether any MSN that entered has tasks waiting for predecessors
+    has_precedence: bool,
+    unfinished: UnfinishedWork,
+    // Rework area or delivery centre of the unfinished work policy
+    exit_area: Option<ExitArea>,
+    // Delivery of every MSN that left the hangar, same order as osw
+    deliveries: Vec<Delivery>,
+    // The MSN at the last station is held there and every SOT works on it
+    held: bool,
+    // Tacts in a row the MSN at the last station was held
+    held_tacts: usize,
+    // MSN held longer than the unfinished work policy allows, which stopped the line
+    stopped: Option<u32>,
 }
 
 impl Hangar{
@@ -18,10 +106,44 @@ impl Hangar{
             stations: vec![],
             tact_time,
             sot: vec![],
-            osw: vec![]
+            osw: vec![],
+            station_ids: Interner::new(),
+            sot_ids: Interner::new(),
+            sot_station_ids: vec![],
+            calendar: None,
+            tact_count: 0,
+            msn_tacts: vec![],
+            msn_learning: vec![],
+            task_osw: vec![],
+            task_workload: vec![],
+            learning: None,
+            version_units: HashMap::new(),
+            sick_leave: None,
+            rng: StdRng::seed_from_u64(0),
+            holidays: HolidayPlan::default(),
+            lending: Lending::default(),
+            hours_per_week: ShiftSystem::default().get_hours_per_week(),
+            overtime_week: None,
+            downtime: vec![],
+            open_shares: vec![],
+            stations_down: vec![],
+            downtime_hours: IndexMap::new(),
+            tact_capacity: 1.0,
+            shifts: None,
+            has_precedence: false,
+            unfinished: UnfinishedWork::default(),
+            exit_area: None,
+            deliveries: vec![],
+            held: false,
+            held_tacts: 0,
+            stopped: None,
         }
     }
 
+    pub fn get_name(&self) -> &str{
+        &self.hangar
+    }
+
     #[cfg(test)]
     pub fn get_station(&self, station_name: &str) -> Option<&Station>{
         for station in self.stations.iter(){
@@ -68,16 +190,159 @@ impl Hangar{
                 return Err(String::from("Station already exists."));
             }
         }
+        self.station_ids.intern(station.get_name());
         self.stations.push(station);
         Ok(())
     }
 
     pub fn set_stations(&mut self, stations: Vec<Station>){
+        self.station_ids = Interner::new();
+        for station in &stations{
+            self.station_ids.intern(station.get_name());
+        }
+        self.stations_down = vec![0; stations.len()];
         self.stations = stations;
+        self.index_sot_stations();
     }
 
     pub fn set_sot(&mut self, sot: Vec<SOT>){
+        self.sot_ids = Interner::new();
+        for s in &sot{
+            self.sot_ids.intern(&s.get_name());
+        }
         self.sot = sot;
+        self.index_sot_stations();
+    }
+
+    /// Downtime blocks work on its stations. Lost hours are reported per reason.
+    pub fn set_downtime(&mut self, downtime: Vec<Downtime>){
+        self.downtime_hours = IndexMap::new();
+        for event in &downtime{
+            self.downtime_hours.entry(event.reason.to_owned()).or_default();
+        }
+        self.downtime = downtime;
+    }
+
+    pub fn get_downtime(&self) -> &Vec<Downtime>{
+        &self.downtime
+    }
+
+    /// A downtime of the station, or of every station if None, starts or ends now.
+    pub fn set_station_down(&mut self, station: Option<&str>, down: bool){
+        for (station_id, count) in self.stations_down.iter_mut().enumerate(){
+            if station.is_none_or(|name| name == self.stations[station_id].get_name()){
+                *count = match down{
+                    true => *count + 1,
+                    false => count.saturating_sub(1),
+                };
+            }
+        }
+    }
+
+    /// Whether the station can be worked on right now.
+    fn get_open_share(&self, station_id: usize) -> f64{
+        match self.stations_down.get(station_id).is_some_and(|count| *count > 0){
+            true => 0.0,
+            false => 1.0,
+        }
+    }
+
+    /// A shift starts or ends now. The first shift that starts sets up the shift timetable.
+    pub fn set_shift(&mut self, shift: &str, on: bool){
+        let shifts = self.shifts.get_or_insert_with(Vec::new);
+        shifts.retain(|name| name != shift);
+        if on{
+            shifts.push(String::from(shift));
+        }
+    }
+
+    /// Works out the open share of every station in the current tact and books the downtime hours.
+    fn plan_downtime(&mut self){
+        let from = self.tact_count as f64 * self.tact_time;
+        let to = from + self.tact_time;
+        self.open_shares = self.stations.iter()
+            .map(|station| {
+                let down: f64 = self.downtime.iter()
+                    .filter(|event| event.affects(station.get_name()))
+                    .map(|event| event.get_overlap(from, to))
+                    .sum();
+                match self.tact_time > 0.0{
+                    true => (1.0 - down / self.tact_time).max(0.0),
+                    false => 1.0,
+                }
+            })
+            .collect();
+        for hours in self.downtime_hours.values_mut(){
+            hours.push(0.0);
+        }
+        for event in &self.downtime{
+            if let Some(hours) = self.downtime_hours.get_mut(&event.reason).and_then(|hours| hours.last_mut()){
+                *hours += event.get_overlap(from, to);
+            }
+        }
+    }
+
+    /// Downtime hours per tact, by reason.
+    pub fn get_downtime_hours(&self) -> &IndexMap<String, Vec<f64>>{
+        &self.downtime_hours
+    }
+
+    fn index_sot_stations(&mut self){
+        self.sot_station_ids = self.sot.iter()
+            .map(|sot| sot.get_stations().iter().map(|name| self.station_ids.get(name)).collect())
+            .collect();
+    }
+
+    pub fn set_calendar(&mut self, calendar: Calendar){
+        self.calendar = Some(calendar);
+    }
+
+    pub fn get_calendar(&self) -> Option<&Calendar>{
+        self.calendar.as_ref()
+    }
+
+    /// Samples absences from the model, the same seed gives the same absences.
+    pub fn set_sick_leave(&mut self, sick_leave: SickLeave, seed: u64){
+        self.sick_leave = Some(sick_leave);
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn set_holidays(&mut self, holidays: HolidayPlan){
+        self.holidays = holidays;
+    }
+
+    pub fn set_learning(&mut self, learning: Learning){
+        self.learning = Some(learning);
+    }
+
+    pub fn set_hours_per_week(&mut self, hours_per_week: f64){
+        self.hours_per_week = hours_per_week;
+    }
+
+    pub fn set_lending(&mut self, lending: Lending){
+        self.lending = lending;
+    }
+
+    pub fn get_lending(&self) -> &Lending{
+        &self.lending
+    }
+
+    pub fn set_unfinished_work(&mut self, unfinished: UnfinishedWork){
+        self.exit_area = unfinished.has_area().then(|| ExitArea::new(&unfinished, self.tact_time));
+        self.unfinished = unfinished;
+    }
+
+    /// Delivery of every MSN that left the hangar.
+    pub fn get_deliveries(&self) -> &Vec<Delivery>{
+        &self.deliveries
+    }
+
+    /// Part of the tact's nominal hours that can be worked, reduced by holidays and shutdowns.
+    fn get_tact_capacity(&self) -> f64{
+        match &self.calendar{
+            Some(calendar) => calendar.get_tact(self.tact_count, self.tact_time).capacity,
+            None => 1.0,
+        }
     }
 
     pub fn get_number_of_stations(&self) -> usize{
@@ -89,7 +354,18 @@ impl Hangar{
         if let Some(msn) = self.stations[len-1].get_current_msn().as_ref(){
             osw = msn.get_workload();
             //print!("OSW: {}", osw);
+            // MSNs leave in the order they entered, one per tact unless the line stopped
+            let planned_exit = (self.osw.len() + len) as f64 * self.tact_time;
+            let exit = (self.tact_count + 1) as f64 * self.tact_time;
+            let delivery = match self.exit_area.as_mut(){
+                Some(area) => area.deliver(osw, exit),
+                None => exit,
+            };
+            self.deliveries.push(Delivery { msn: msn.get_msn(), osw, planned_exit, exit, delivery });
             self.osw.push(osw);
+            self.msn_tacts.push((msn.get_entry_tact(), self.tact_count));
+            self.msn_learning.push(msn.get_learning_factor());
+            self.task_osw.push(msn.get_all_tasks().iter().map(|task| task.get_workload()).collect());
         }
     }
 
@@ -127,62 +403,297 @@ impl Hangar{
         tasklist
     }
 
-    fn work_on_osw(&mut self, sot: &mut SOT, initial_remaining_time: f64) -> f64{
-        let mut remaining_time = initial_remaining_time;
-        let station_names = sot.get_stations();
-        for station_name in station_names{
-            if remaining_time > 0.0{
-                let station = self.get_station_mut(&station_name);
-                if let Some(station) = station{
-                    let msn = station.get_msn_mut();
-                    if let Some(msn) = msn{
-                        let tasks = msn.get_sot_tasks(sot);
-                        remaining_time = sot.work(tasks, remaining_time);
-                    }
+    /// Unfinished tasks the SOT can work on, per station from the end of the line.
+    fn get_jobs(&self, sot_id: usize, sot: &SOT) -> Vec<Job>{
+        let assigned = &self.sot_station_ids[sot_id];
+        if let Some(i) = assigned.iter().position(|station_id| station_id.is_none()){
+            panic!("Station {} not found in hangar in hangar.tact().\nMake sure all stations required in sot.csv are also listed in stations.csv.", sot.get_stations()[i]);
+        }
+        let mut jobs = vec![];
+        let last = self.stations.len().saturating_sub(1);
+        for (station_id, station) in self.stations.iter().enumerate().rev(){
+            if let Some(msn) = station.get_current_msn(){
+                let mut assignment = assigned.iter().position(|id| *id == Some(station_id));
+                // The line stands still, so every SOT works off its tasks of the held MSN
+                if self.held && station_id == last{
+                    assignment = assignment.or(Some(assigned.len()));
+                }
+                msn.add_jobs(station_id, self.station_ids.get_name(station_id), sot_id, assignment, &mut jobs);
+            }
+        }
+        jobs
+    }
+
+    fn count_blocked(&self, sot_id: usize) -> usize{
+        self.stations.iter()
+            .filter_map(|station| station.get_current_msn())
+            .map(|msn| msn.count_blocked(sot_id))
+            .sum()
+    }
+
+    /// The SOT works on the tasks in the order of its dispatch rule until its time is up.
+    fn dispatch(&mut self, sot_id: usize, sot: &mut SOT){
+        loop{
+            let blocked = match self.has_precedence{
+                true => self.count_blocked(sot_id),
+                false => 0,
+            };
+            let batches = sot.get_dispatch_rule().order(self.get_jobs(sot_id, sot));
+            for batch in batches{
+                if sot.get_remaining_time() <= 0.0{
+                    break;
                 }
-                else{
-                    panic!("Station {} not found in hangar in hangar.tact().\nMake sure all stations required in sot.csv are also listed in stations.csv.", station_name);
+                let open_share = self.get_open_share(batch.station_id);
+                if let Some(msn) = self.stations[batch.station_id].get_msn_mut(){
+                    let tasks = msn.pick_tasks(&batch.positions);
+                    sot.work_at_station(tasks, batch.station_id, open_share);
                 }
             }
-            else{
+            // Finished tasks may free successors, which are worked on in the same tact
+            if blocked == 0 || sot.get_remaining_time() <= 0.0 || self.count_blocked(sot_id) == blocked{
                 break;
             }
         }
-        remaining_time
     }
 
-    pub fn tact(& mut self){
-        // Let All SOTs work on the stations in the hangar
-        // Take SOTs out of Hangar to prevent data races (Rust rules)
-        let mut sot = std::mem::replace(&mut self.sot, vec![]);
-        //println!("{}", sot.len());
-        for sot in &mut sot{
-            //Get All tasks for that SOT in the whole hangar
-            let mut tasklist: Vec<&mut Task> = vec![];
-            for station in self.stations.iter_mut().rev(){
-                let station_name = station.get_name().to_owned();
-                let msn = station.get_msn_mut();
-                if let Some(msn) = msn {
-                    tasklist.append(&mut msn.get_tasks(&station_name, sot));
+    /// SOTs with time left work on the other SOTs' tasks on the stations the lending policy reaches,
+    /// oldest MSN first.
+    fn lend_idle_workers(&mut self, sot: &mut [SOT]){
+        'lender: for lender_id in 0..sot.len(){
+            let assigned: Vec<usize> = self.sot_station_ids[lender_id].iter().flatten().copied().collect();
+            let reachable = self.lending.get_reachable_stations(&assigned, self.stations.len());
+            for station_id in reachable.into_iter().rev(){
+                for borrower_id in (0..sot.len()).filter(|id| *id != lender_id){
+                    if sot[lender_id].get_remaining_time() <= 0.0{
+                        continue 'lender;
+                    }
+                    let station_name = self.station_ids.get_name(station_id);
+                    let open_share = self.get_open_share(station_id);
+                    if let Some(msn) = self.stations[station_id].get_msn_mut(){
+                        let tasks = msn.get_indexed_tasks(station_id, station_name, borrower_id);
+                        let hours = sot[lender_id].lend(tasks, self.lending.efficiency, station_id, open_share);
+                        sot[borrower_id].add_borrowed(hours);
+                    }
                 }
             }
-            //Work on Main Station
-            let mut remaining_time = sot.work(tasklist, self.tact_time);
+        }
+    }
+
+    /// Week of the current tact for the overtime limits.
+    fn get_week(&self) -> usize{
+        match &self.calendar{
+            Some(calendar) => {
+                let week = calendar.get_tact(self.tact_count, self.tact_time).start.iso_week();
+                (week.year() * 100) as usize + week.week() as usize
+            }
+            None => (self.tact_count as f64 * self.tact_time / self.hours_per_week).floor() as usize,
+        }
+    }
 
-            //Burn OSW from previous Stations with remaining time
-            if remaining_time > 0.0{
-                remaining_time = self.work_on_osw(sot, remaining_time);
-                
+    /// SOTs with an overtime policy work overtime on the tasks that would travel from their stations.
+    fn work_overtime(&mut self, sot: &mut [SOT]){
+        let week = self.get_week();
+        if self.overtime_week != Some(week){
+            sot.iter_mut().for_each(|s| s.start_week());
+            self.overtime_week = Some(week);
+        }
+        for (sot_id, sot) in sot.iter_mut().enumerate().filter(|(_, s)| s.get_overtime().is_some()){
+            let assigned = &self.sot_station_ids[sot_id];
+            // Tasks per station with its open share, a station that is down gets no overtime either
+            let mut stations: Vec<(f64, Vec<&mut Task>)> = vec![];
+            for (station_id, station) in self.stations.iter_mut().enumerate().rev(){
+                if !assigned.contains(&Some(station_id)){
+                    continue;
+                }
+                let station_name = self.station_ids.get_name(station_id);
+                if let Some(msn) = station.get_msn_mut(){
+                    let tasks = msn.get_indexed_tasks(station_id, station_name, sot_id);
+                    let due: Vec<&mut Task> = tasks.into_iter().filter(|task| task.is_last_station(station_name) && task.get_workload() > 0.0).collect();
+                    stations.push((self.open_shares[station_id], due));
+                }
             }
-            sot.add_utilization((self.tact_time - remaining_time)/self.tact_time);
+            let due_workload: f64 = stations.iter().flat_map(|(_, tasks)| tasks.iter()).map(|task| task.get_workload()).sum();
+            sot.work_overtime(stations, due_workload);
         }
+    }
+
+    /// Runs a whole tact without events in it: absences start with the tact, shifts are averaged
+    /// over it and stations aren't down. See start_tact, work_tact and end_tact.
+    pub fn tact(&mut self){
+        for worker in self.start_tact(){
+            self.set_absent(&worker);
+        }
+        self.work_tact(self.tact_time);
+        self.end_tact();
+    }
+
+    /// Starts a tact: applies staffing and holidays, books the tact's downtime and draws absences.
+    /// Returns the workers off sick in the tact, who are present until set_absent is called.
+    pub fn start_tact(&mut self) -> Vec<String>{
+        self.tact_capacity = self.get_tact_capacity();
+        let month = self.calendar.as_ref().map(|calendar| calendar.get_tact(self.tact_count, self.tact_time).start.month());
+        for s in self.sot.iter_mut(){
+            s.apply_staffing(self.tact_count);
+        }
+        if let Some(calendar) = self.calendar.as_ref().filter(|_| !self.holidays.is_empty()){
+            let days = calendar.get_tact_days(self.tact_count, self.tact_time);
+            for s in self.sot.iter_mut(){
+                s.plan_holidays(&self.holidays, &days);
+            }
+        }
+        self.plan_downtime();
+        if self.has_precedence{
+            for msn in self.stations.iter_mut().filter_map(|station| station.get_msn_mut().as_mut()){
+                msn.plan_critical_paths();
+            }
+        }
+        let mut sick = vec![];
+        for sot in self.sot.iter_mut(){
+            if let Some(sick_leave) = &self.sick_leave{
+                sick.extend(sot.sample_absence(sick_leave, month, &mut self.rng));
+            }
+            sot.open_tact();
+        }
+        sick
+    }
+
+    /// A worker is off from now on until the next tact starts.
+    pub fn set_absent(&mut self, worker: &str){
+        for sot in self.sot.iter_mut(){
+            if sot.set_absent(worker){
+                return;
+            }
+        }
+    }
+
+    /// Works a slice of the current tact with the given line hours, with the shifts, absences and
+    /// downtime as they are now: every SOT works on its tasks and lends idle workers.
+    /// Returns the tasks completed in it.
+    pub fn work_tact(&mut self, hours: f64) -> Vec<Completion>{
+        // Let All SOTs work on the stations in the hangar
+        // Take SOTs out of Hangar to prevent data races (Rust rules)
+        let mut sot = std::mem::take(&mut self.sot);
+        for (sot_id, sot) in sot.iter_mut().enumerate(){
+            //Work on Main Station, only during the SOT's shifts and working days
+            sot.start_slice(hours * self.tact_capacity, self.shifts.as_deref());
+            //Work on the SOT's tasks in the whole hangar and burn OSW from previous stations
+            self.dispatch(sot_id, sot);
+        }
+        if self.lending.is_enabled(){
+            self.lend_idle_workers(&mut sot);
+        }
+        if self.has_precedence{
+            self.book_blocked(&mut sot);
+        }
+        sot.iter_mut().for_each(|s| s.end_slice());
         self.sot = sot; //Return SOT after manipulation
+        self.take_completions()
+    }
+
+    fn take_completions(&mut self) -> Vec<Completion>{
+        let mut completed = vec![];
+        for station in self.stations.iter_mut(){
+            if let Some(msn) = station.get_msn_mut(){
+                let number = msn.get_msn();
+                completed.extend(msn.take_completed_tasks().into_iter().map(|(sot, share)| Completion { msn: number, sot, share }));
+            }
+        }
+        completed
+    }
+
+    /// Ends the tact worked with work_tact: SOTs work overtime, then all MSNs move one station on.
+    pub fn end_tact(&mut self) -> TactOutcome{
+        let mut sot = std::mem::take(&mut self.sot);
+        if sot.iter().any(|s| s.get_overtime().is_some()){
+            self.work_overtime(&mut sot);
+        }
+        sot.iter_mut().for_each(|s| s.end_tact());
+        self.sot = sot;
+        let mut outcome = TactOutcome { completed: self.take_completions(), ..TactOutcome::default() };
+        // A held MSN stays until its work is done, it only leaves with OSW that no SOT can ever work off,
+        // also while its station is down for good
+        let exit_station = self.stations.last();
+        let exit_msn = exit_station.and_then(|station| station.get_current_msn());
+        let tact_end = (self.tact_count + 1) as f64 * self.tact_time;
+        self.held = self.unfinished.policy == ExitPolicy::Hold
+            && exit_station.is_some_and(|station| !self.is_down_for_good(station.get_name(), tact_end))
+            && exit_msn.is_some_and(|msn| msn.get_workload() > 0.0 && msn.can_progress(|task| self.can_work_on(task)));
+        self.held_tacts = match self.held{
+            true => self.held_tacts + 1,
+            false => 0,
+        };
+        if self.held_tacts > self.unfinished.max_held_tacts{
+            self.stopped = exit_msn.map(|msn| msn.get_msn());
+        }
+        outcome.held = self.held;
+        if !self.held{
+            outcome.exited = exit_msn.map(|msn| msn.get_msn());
+            //Removes the last MSN (to sink) and moves all MSN one station further.
+            self.move_all_msn();
+        }
+        self.tact_count += 1;
+        outcome
+    }
+
+    /// Whether a downtime of the station started by the given line hour and has no finite end.
+    fn is_down_for_good(&self, station: &str, until: f64) -> bool{
+        self.downtime.iter()
+            .any(|event| event.affects(station) && event.start <= until && !(event.start + event.duration).is_finite())
+    }
 
-        //Removes the last MSN (to sink) and moves all MSN one station further.
-        self.move_all_msn();
+    /// The MSN that was held at the last station for more tacts in a row than the unfinished work
+    /// policy allows. The line stops with it.
+    pub fn get_stopped_msn(&self) -> Option<u32>{
+        self.stopped
     }
 
-    pub fn insert_msn(&mut self, msn: MSN){
+    /// Whether a SOT of the hangar can work on the task now or later. A SOT whose workers are always
+    /// off sick never can.
+    fn can_work_on(&self, task: &Task) -> bool{
+        let Some(sot) = self.sot_ids.get(task.get_sot()).map(|sot_id| &self.sot[sot_id]) else{
+            return false;
+        };
+        let months: Vec<Option<u32>> = match self.calendar{
+            Some(_) => (1..=12).map(Some).collect(),
+            None => vec![None],
+        };
+        let always_sick = self.sick_leave.as_ref()
+            .is_some_and(|sick_leave| months.iter().all(|month| sick_leave.get_rate(&sot.get_name(), *month) >= 1.0));
+        !always_sick && sot.can_work_on(task, self.tact_count)
+    }
+
+    /// Books the time SOTs have left in the slice while tasks they could work on wait for predecessors
+    /// as blocked, as far as the workers could have finished those tasks in it.
+    fn book_blocked(&self, sot: &mut [SOT]){
+        for (sot_id, sot) in sot.iter_mut().enumerate(){
+            let remaining_time = sot.get_remaining_time();
+            let rate = sot.get_rate();
+            if remaining_time <= 0.0 || rate <= 0.0{
+                continue;
+            }
+            let assigned = &self.sot_station_ids[sot_id];
+            let blocked_workload: f64 = self.stations.iter().enumerate()
+                .filter_map(|(station_id, station)| station.get_current_msn().map(|msn| (station_id, msn)))
+                .map(|(station_id, msn)| match assigned.contains(&Some(station_id)){
+                    // Open work of the MSN can be burned at the SOT's own stations
+                    true => msn.get_blocked_sot_workload(sot_id),
+                    false => msn.get_blocked_workload(station_id, sot_id),
+                })
+                .sum();
+            sot.add_blocked((blocked_workload / rate).min(remaining_time));
+        }
+    }
+
+    pub fn insert_msn(&mut self, mut msn: MSN){
+        self.has_precedence |= msn.has_precedence();
+        msn.build_index(&self.station_ids, &self.sot_ids);
+        msn.set_entry_tact(self.tact_count);
+        if let Some(learning) = &self.learning{
+            let unit = self.version_units.entry(msn.get_version().to_owned()).or_insert(0);
+            *unit += 1;
+            msn.apply_learning(learning, *unit);
+        }
+        self.task_workload.push(msn.get_all_tasks().iter().map(|task| task.get_workload()).collect());
         let first_station = &mut self.stations[0];
         first_station.set_current_msn(msn);
     }
@@ -191,6 +702,41 @@ impl Hangar{
         &self.osw
     }
 
+    /// Entry date and exit date of every MSN that left the hangar, empty without a calendar.
+    pub fn get_msn_dates(&self) -> Vec<(NaiveDate, NaiveDate)>{
+        match &self.calendar{
+            Some(calendar) => self.msn_tacts.iter()
+                .map(|(entry, exit)| (calendar.get_tact(*entry, self.tact_time).start, calendar.get_tact(*exit, self.tact_time).end))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Learning factor of every MSN that left the hangar, 1 without a learning curve.
+    pub fn get_learning_factors(&self) -> &Vec<f64>{
+        &self.msn_learning
+    }
+
+    /// Workload left of every task of every MSN that left the hangar, in task order.
+    pub fn get_task_osw(&self) -> &Vec<Vec<f64>>{
+        &self.task_osw
+    }
+
+    /// Workload of every task of every MSN when it entered the hangar, after learning, in task order.
+    pub fn get_task_workload(&self) -> &Vec<Vec<f64>>{
+        &self.task_workload
+    }
+
+    pub fn has_precedence(&self) -> bool{
+        self.has_precedence
+    }
+
+    /// Share of the available time per tact every SOT was blocked by task precedence, by SOT.
+    /// Idle time is what is neither utilized nor blocked.
+    pub fn get_blocked_shares(&self) -> IndexMap<String, Vec<f64>>{
+        self.sot.iter().map(|sot| (sot.get_name(), sot.get_blocked().to_owned())).collect()
+    }
+
     pub fn get_sot_utilization(&self) -> IndexMap<String, Vec<f64>>{
         //Get SOT Names
         let mut sot_util_map: IndexMap<String, Vec<f64>> = IndexMap::new();
@@ -199,6 +745,56 @@ impl Hangar{
 
         sot_util_map
     }
+
+    /// Worked hours per tact of every worker, by worker id.
+    pub fn get_worker_hours(&self) -> IndexMap<String, Vec<f64>>{
+        let mut worker_hours: IndexMap<String, Vec<f64>> = IndexMap::new();
+        for worker in self.sot.iter().flat_map(|sot| sot.get_workers()){
+            worker_hours.insert(worker.get_id().to_owned(), worker.get_hours().to_owned());
+        }
+        worker_hours
+    }
+
+    /// Overtime worker hours of every SOT per tact.
+    pub fn get_overtime_hours(&self) -> IndexMap<String, Vec<f64>>{
+        let mut overtime: IndexMap<String, Vec<f64>> = IndexMap::new();
+        for sot in self.sot.iter().filter(|sot| sot.get_overtime().is_some()){
+            overtime.insert(sot.get_name(), sot.get_overtime_hours().to_owned());
+        }
+        overtime
+    }
+
+    pub fn get_total_overtime(&self) -> f64{
+        self.sot.iter().flat_map(|sot| sot.get_overtime_hours()).sum()
+    }
+
+    pub fn get_overtime_cost(&self) -> f64{
+        self.sot.iter().map(|sot| sot.get_overtime_cost()).sum()
+    }
+
+    /// Hours each capacity factor took away from every SOT over the run.
+    pub fn get_capacity_waterfall(&self) -> IndexMap<String, CapacityWaterfall>{
+        self.sot.iter().map(|sot| (sot.get_name(), sot.get_waterfall().to_owned())).collect()
+    }
+
+    /// Hours every SOT lent to and borrowed from other SOTs per tact.
+    pub fn get_lent_hours(&self) -> IndexMap<String, Vec<f64>>{
+        let mut lending: IndexMap<String, Vec<f64>> = IndexMap::new();
+        for sot in self.sot.iter(){
+            lending.insert(format!("{} lent", sot.get_name()), sot.get_lent().to_owned());
+            lending.insert(format!("{} borrowed", sot.get_name()), sot.get_borrowed().to_owned());
+        }
+        lending
+    }
+
+    /// Mean utilization over all SOTs and tacts.
+    pub fn get_mean_utilization(&self) -> f64{
+        let utilization: Vec<f64> = self.sot.iter().flat_map(|sot| sot.get_utilization()).collect();
+        if utilization.is_empty(){
+            return 0.0;
+        }
+        utilization.iter().sum::<f64>() / utilization.len() as f64
+    }
 }
 
 impl fmt::Display for Hangar{
@@ -214,6 +810,9 @@ impl fmt::Display for Hangar{
 #[cfg(test)]
 mod tests{
     use crate::util;
+    use crate::capacity::CapacityFactors;
+    use crate::dispatch::Dispatch;
+    use crate::datasource::fixture;
 
     use super::*;
 
@@ -221,8 +820,8 @@ mod tests{
     fn msn_insertion_works(){
         let mut hangar = Hangar::new("1", 1.0);
         let station = Station::new("1");
-        hangar.add_station(station);
-        let msn = util::get_tactplan()[0].to_owned();
+        hangar.add_station(station).unwrap();
+        let msn = util::get_tactplan(&fixture(), "1").unwrap()[0].to_owned();
         hangar.insert_msn(msn);
         assert!(hangar.stations[0].get_current_msn().is_some());
     }
@@ -230,8 +829,8 @@ mod tests{
     #[test]
     fn tacting_works(){
         let mut hangar = Hangar::new("1", 1.0);
-        hangar.set_stations(util::get_stations()[0..3].to_vec());
-        let msn = util::get_tactplan()[0].to_owned();
+        hangar.set_stations(util::get_stations(&fixture(), "1").unwrap()[0..3].to_vec());
+        let msn = util::get_tactplan(&fixture(), "1").unwrap()[0].to_owned();
         hangar.insert_msn(msn);
         hangar.tact();
         assert!(hangar.stations[0].get_current_msn().is_none());
@@ -239,11 +838,35 @@ mod tests{
         assert!(hangar.stations[2].get_current_msn().is_none());
     }
 
+    #[test]
+    fn shutdown_leaves_workload_open(){
+        use crate::calendar::{CalendarException, ExceptionKind};
+        let start = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let mut results = vec![];
+        for shutdown in [false, true]{
+            let mut calendar = Calendar::new(start, &Calendar::default_working_days(5.0), 10.0);
+            if shutdown{
+                calendar.add_exception(CalendarException { date: start, kind: ExceptionKind::Shutdown, capacity: 0.0, reason: String::new() });
+            }
+            let mut hangar = Hangar::new("1", 10.0);
+            hangar.set_calendar(calendar);
+            hangar.set_stations(util::get_stations(&fixture(), "1").unwrap());
+            hangar.set_sot(util::load_sots(&fixture(), "1", &CapacityFactors::new(1.0), Dispatch::default(), &Default::default()).unwrap());
+            hangar.insert_msn(util::get_tactplan(&fixture(), "1").unwrap()[0].to_owned());
+            for _ in 0..4{
+                hangar.tact();
+            }
+            results.push((hangar.get_osw()[0], hangar.get_msn_dates()[0]));
+        }
+        assert!(results[1].0 > results[0].0);
+        assert_eq!(results[0].1, (start, NaiveDate::from_ymd_opt(2026, 1, 8).unwrap()));
+    }
+
     #[test]
     fn osw_calculation_works(){
         let mut hangar = Hangar::new("1", 1.0);
-        hangar.set_stations(util::get_stations()[0..3].to_vec());
-        let msn = util::get_tactplan()[0].to_owned();
+        hangar.set_stations(util::get_stations(&fixture(), "1").unwrap()[0..3].to_vec());
+        let msn = util::get_tactplan(&fixture(), "1").unwrap()[0].to_owned();
         hangar.insert_msn(msn);
         for _ in 0..3{
             hangar.tact();
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use chrono::NaiveDate;
+
+/// Planned holiday from holidays.csv. Either a named worker, or a number of workers of a SOT
+/// (all of its workers if no headcount is given).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holiday{
+    pub worker: Option<String>,
+    pub sot: Option<String>,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub headcount: Option<u32>,
+}
+
+impl Holiday{
+    fn covers(&self, date: NaiveDate) -> bool{
+        self.start <= date && date <= self.end
+    }
+}
+
+/// Holidays indexed by worker and by SOT.
+#[derive(Debug, Clone, Default)]
+pub struct HolidayPlan{
+    by_worker: HashMap<String, Vec<Holiday>>,
+    by_sot: HashMap<String, Vec<Holiday>>,
+}
+
+impl HolidayPlan{
+    pub fn new(holidays: Vec<Holiday>) -> Self{
+        let mut plan = HolidayPlan::default();
+        for holiday in holidays{
+            match (&holiday.worker, &holiday.sot){
+                (Some(worker), _) => plan.by_worker.entry(worker.to_owned()).or_default().push(holiday),
+                (None, Some(sot)) => plan.by_sot.entry(sot.to_owned()).or_default().push(holiday),
+                (None, None) => {}
+            }
+        }
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.by_worker.is_empty() && self.by_sot.is_empty()
+    }
+
+    /// Whether the worker is on a personal holiday or the whole SOT is off.
+    pub fn is_worker_off(&self, worker: &str, sot: &str, date: NaiveDate) -> bool{
+        let personal = self.by_worker.get(worker)
+            .is_some_and(|holidays| holidays.iter().any(|holiday| holiday.covers(date)));
+        let whole_sot = self.by_sot.get(sot)
+            .is_some_and(|holidays| holidays.iter().any(|holiday| holiday.headcount.is_none() && holiday.covers(date)));
+        personal || whole_sot
+    }
+
+    /// Number of anonymous workers of the SOT on holiday on the date.
+    pub fn get_headcount_off(&self, sot: &str, date: NaiveDate) -> u32{
+        match self.by_sot.get(sot){
+            Some(holidays) => holidays.iter()
+                .filter(|holiday| holiday.covers(date))
+                .filter_map(|holiday| holiday.headcount)
+                .sum(),
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn date(d: u32) -> NaiveDate{
+        NaiveDate::from_ymd_opt(2026, 8, d).unwrap()
+    }
+
+    #[test]
+    fn worker_and_sot_holidays(){
+        let plan = HolidayPlan::new(vec![
+            Holiday { worker: Some(String::from("W1")), sot: None, start: date(3), end: date(14), headcount: None },
+            Holiday { worker: None, sot: Some(String::from("MECH")), start: date(10), end: date(14), headcount: Some(2) },
+            Holiday { worker: None, sot: Some(String::from("MECH")), start: date(12), end: date(12), headcount: Some(1) },
+            Holiday { worker: None, sot: Some(String::from("ELEC")), start: date(17), end: date(21), headcount: None },
+        ]);
+        assert!(plan.is_worker_off("W1", "MECH", date(3)));
+        assert!(!plan.is_worker_off("W1", "MECH", date(15)));
+        assert!(plan.is_worker_off("W7", "ELEC", date(18)));
+        assert_eq!(plan.get_headcount_off("MECH", date(12)), 3);
+        assert_eq!(plan.get_headcount_off("MECH", date(15)), 0);
+    }
+}
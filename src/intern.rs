@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+/// Maps names to dense integer ids, so the tact loop compares integers instead of strings.
+#[derive(Debug, Clone, Default)]
+pub struct Interner{
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl Interner{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// Returns the id of the name, adding it if it is not known yet.
+    pub fn intern(&mut self, name: &str) -> usize{
+        if let Some(id) = self.ids.get(name){
+            return *id;
+        }
+        let id = self.names.len();
+        self.names.push(String::from(name));
+        self.ids.insert(String::from(name), id);
+        id
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize>{
+        self.ids.get(name).copied()
+    }
+
+    pub fn get_name(&self, id: usize) -> &str{
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize{
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.names.is_empty()
+    }
+}
+
+/// Mutable references to the elements at the given indices.
+/// The indices must be sorted ascending and unique.
+pub fn pick_mut<'a, T>(items: &'a mut [T], indices: &[usize]) -> Vec<&'a mut T>{
+    let mut picked = Vec::with_capacity(indices.len());
+    let mut rest = items;
+    let mut offset = 0;
+    for &index in indices{
+        let (_, tail) = rest.split_at_mut(index - offset);
+        let (item, tail) = tail.split_first_mut().expect("Index out of range in pick_mut");
+        picked.push(item);
+        rest = tail;
+        offset = index + 1;
+    }
+    picked
+}
+
+/// Same as pick_mut, for unique indices in any order. The elements come in the order of the indices.
+pub fn pick_mut_ordered<'a, T>(items: &'a mut [T], indices: &[usize]) -> Vec<&'a mut T>{
+    let mut order: Vec<usize> = (0..indices.len()).collect();
+    order.sort_by_key(|&i| indices[i]);
+    let sorted: Vec<usize> = order.iter().map(|&i| indices[i]).collect();
+    let mut slots: Vec<Option<&'a mut T>> = (0..indices.len()).map(|_| None).collect();
+    for (slot, item) in order.into_iter().zip(pick_mut(items, &sorted)){
+        slots[slot] = Some(item);
+    }
+    slots.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn interning_is_stable(){
+        let mut names = Interner::new();
+        assert_eq!(names.intern("S1"), 0);
+        assert_eq!(names.intern("S2"), 1);
+        assert_eq!(names.intern("S1"), 0);
+        assert_eq!(names.get("S2"), Some(1));
+        assert_eq!(names.get("S3"), None);
+        assert_eq!(names.get_name(1), "S2");
+    }
+
+    #[test]
+    fn pick_mut_returns_requested_items(){
+        let mut items = vec![10, 20, 30, 40, 50];
+        for item in pick_mut(&mut items, &[1, 2, 4]){
+            *item += 1;
+        }
+        assert_eq!(items, vec![10, 21, 31, 40, 51]);
+
+        let picked: Vec<i32> = pick_mut_ordered(&mut items, &[4, 0, 2]).into_iter().map(|item| *item).collect();
+        assert_eq!(picked, vec![51, 10, 31]);
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+/// How the learning rate is applied to the unit number.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LearningModel{
+    // The cumulative average time falls by the learning rate with every doubling of units
+    Wright,
+    // The time of a single unit falls by the learning rate with every doubling of units
+    Crawford,
+}
+
+impl LearningModel{
+    /// Time of unit n relative to the first unit.
+    fn get_unit_time(&self, rate: f64, unit: u32) -> f64{
+        let exponent = rate.log2();
+        let n = unit.max(1) as f64;
+        match self{
+            LearningModel::Crawford => n.powf(exponent),
+            LearningModel::Wright => n.powf(exponent + 1.0) - (n - 1.0).powf(exponent + 1.0),
+        }
+    }
+}
+
+/// Learning rate of one SOT (all SOTs if None) on one version (all versions if None).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearningCurve{
+    pub sot: Option<String>,
+    pub version: Option<String>,
+    pub rate: f64,
+    pub floor: Option<f64>,
+}
+
+/// Learning curves per SOT and version. The floor is the unit time, relative to the first unit,
+/// at which learning stops; tasks.csv gives the workload at the floor, so earlier units need
+/// more. The most specific curve wins: SOT and version, SOT only, version only, then the default rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Learning{
+    model: LearningModel,
+    curves: Vec<LearningCurve>,
+    default_rate: Option<f64>,
+    default_floor: f64,
+}
+
+impl Learning{
+    pub fn new(model: LearningModel, curves: Vec<LearningCurve>, default_rate: Option<f64>, default_floor: f64) -> Self{
+        Self { model, curves, default_rate, default_floor }
+    }
+
+    /// Rate and floor of the SOT on the version, None if the SOT doesn't learn on it.
+    pub fn get_curve(&self, sot: &str, version: &str) -> Option<(f64, f64)>{
+        let candidates = [(Some(sot), Some(version)), (Some(sot), None), (None, Some(version)), (None, None)];
+        let curve = candidates.iter().find_map(|(sot, version)| self.curves.iter()
+            .find(|curve| curve.sot.as_deref() == *sot && curve.version.as_deref() == *version));
+        match curve{
+            Some(curve) => Some((curve.rate, curve.floor.unwrap_or(self.default_floor))),
+            None => self.default_rate.map(|rate| (rate, self.default_floor)),
+        }
+    }
+
+    /// Workload multiplier of the SOT for the given unit of the version, 1 once the floor is reached.
+    pub fn get_factor(&self, sot: &str, version: &str, unit: u32) -> f64{
+        match self.get_curve(sot, version){
+            Some((rate, floor)) if floor > 0.0 => self.model.get_unit_time(rate, unit).max(floor) / floor,
+            _ => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn factor_falls_to_floor(){
+        let learning = Learning::new(LearningModel::Crawford, vec![], Some(0.8), 0.5);
+        assert_eq!(learning.get_factor("MECH", "A", 1), 2.0);
+        assert!((learning.get_factor("MECH", "A", 2) - 1.6).abs() < 1e-9);
+        assert_eq!(learning.get_factor("MECH", "A", 100), 1.0);
+
+        // Wright's first unit is the same, later units are cheaper than with Crawford
+        let wright = Learning::new(LearningModel::Wright, vec![], Some(0.8), 0.5);
+        assert_eq!(wright.get_factor("MECH", "A", 1), 2.0);
+        assert!(wright.get_factor("MECH", "A", 2) < 1.6);
+    }
+
+    #[test]
+    fn most_specific_curve_wins(){
+        let learning = Learning::new(LearningModel::Crawford, vec![
+            LearningCurve { sot: Some(String::from("MECH")), version: Some(String::from("B")), rate: 0.9, floor: Some(0.7) },
+            LearningCurve { sot: None, version: Some(String::from("B")), rate: 0.85, floor: None },
+        ], None, 0.5);
+        assert_eq!(learning.get_curve("MECH", "B"), Some((0.9, 0.7)));
+        assert_eq!(learning.get_curve("ELEC", "B"), Some((0.85, 0.5)));
+        assert_eq!(learning.get_curve("ELEC", "A"), None);
+        assert_eq!(learning.get_factor("ELEC", "A", 1), 1.0);
+    }
+}
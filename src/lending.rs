@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+/// Where idle workers of a SOT may help other SOTs.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LendingPolicy{
+    // Idle time is lost
+    Off,
+    // Stations the lending SOT is assigned to
+    SameStation,
+    // Its stations and the stations directly before and after them
+    Adjacent,
+}
+
+impl LendingPolicy{
+    pub fn parse(policy: &str) -> Option<Self>{
+        match policy{
+            "off" => Some(LendingPolicy::Off),
+            "same_station" | "same-station" => Some(LendingPolicy::SameStation),
+            "adjacent" => Some(LendingPolicy::Adjacent),
+            _ => None,
+        }
+    }
+}
+
+/// Lending of idle workers to other SOTs after every SOT worked on its own tasks in a tact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Lending{
+    pub policy: LendingPolicy,
+    // Productivity of lent workers relative to their home SOT
+    pub efficiency: f64,
+}
+
+impl Default for Lending{
+    fn default() -> Self{
+        Self { policy: LendingPolicy::Off, efficiency: 0.8 }
+    }
+}
+
+impl Lending{
+    pub fn is_enabled(&self) -> bool{
+        self.policy != LendingPolicy::Off
+    }
+
+    /// Station ids a SOT assigned to the given station ids can help on, in line order.
+    pub fn get_reachable_stations(&self, assigned: &[usize], number_of_stations: usize) -> Vec<usize>{
+        let reach = match self.policy{
+            LendingPolicy::Off => return vec![],
+            LendingPolicy::SameStation => 0,
+            LendingPolicy::Adjacent => 1,
+        };
+        (0..number_of_stations)
+            .filter(|station| assigned.iter().any(|a| a.abs_diff(*station) <= reach))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn adjacent_reaches_neighbours(){
+        let mut lending = Lending::default();
+        assert!(lending.get_reachable_stations(&[2], 5).is_empty());
+        lending.policy = LendingPolicy::SameStation;
+        assert_eq!(lending.get_reachable_stations(&[2], 5), vec![2]);
+        lending.policy = LendingPolicy::Adjacent;
+        assert_eq!(lending.get_reachable_stations(&[0, 4], 5), vec![0, 1, 3, 4]);
+    }
+}
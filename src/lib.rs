@@ -5,4 +5,24 @@ pub mod hangar;
 pub mod util;
 pub mod sot;
 pub mod settings;
+pub mod error;
+pub mod datasource;
+pub mod validate;
+pub mod intern;
+pub mod shift;
+pub mod calendar;
+pub mod worker;
+pub mod absence;
+pub mod holiday;
+pub mod lending;
+pub mod learning;
+pub mod overtime;
+pub mod staffing;
+pub mod downtime;
+pub mod capacity;
+pub mod engine;
+pub mod schedule;
+pub mod dispatch;
+pub mod unfinished;
+pub mod comparison;
 pub mod sim;
\ No newline at end of file
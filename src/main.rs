@@ -6,27 +6,395 @@ mod util;
 mod sim;
 mod sot;
 mod settings;
+mod error;
+mod datasource;
+mod validate;
+mod intern;
+mod shift;
+mod calendar;
+mod worker;
+mod absence;
+mod holiday;
+mod lending;
+mod learning;
+mod overtime;
+mod staffing;
+mod downtime;
+mod capacity;
+mod engine;
+mod schedule;
+mod dispatch;
+mod unfinished;
+mod comparison;
+mod cli;
 
-fn main() {
-    print!("Please make sure, the input data of the files with the exact names\n
-output.csv\nsot.csv\nstations.csv\ntactplan.csv\ntasks.csv\n
-are in the folder ./data. The columns of the files must be as follows:\n
-output.csv: (Generated through this program, so no need to pay attention) \nsot.csv: sot, bc, cvat, stations\nstations.csv: station\ntactplan.csv: msn, version\ntasks.csv: station, sot, version, workload\n
-Hardcoded Parameters are:
-TactTime: 9.333 hours (R7.5 in 2 Shift)\nInefficiency to due to BC Absence: 8.6%\n
-If you get unexpected results, please make sure the data is correct\n(e.g. task data only contains data for one hangar.)\n\nSimulation Log:\n");
-    let mut sim = sim::SIM::new();
-
-    //Need to perform settings here
-    sim.add_hangar("8");
+use cli::{Command, Format, Options, SweepParameter, SweepSpec};
+use datasource::OutputSink;
+use error::PpeError;
+use settings::Settings;
+use std::path::Path;
+
+// Name and function of a statistic over the values of several runs
+type Statistic = (&'static str, fn(&[f64]) -> f64);
+
+struct Summary{
+    label: String,
+    msns: usize,
+    total_osw: f64,
+    max_osw: f64,
+    mean_utilization: f64,
+    overtime_hours: f64,
+    overtime_cost: f64,
+}
+
+impl Summary{
+    fn from_sim(label: &str, sim: &sim::SIM) -> Self{
+        Summary::new(label, sim.get_osw(), sim.get_mean_utilization(), sim.get_overtime())
+    }
 
+    fn from_hangar(hangar: &hangar::Hangar) -> Self{
+        let overtime = (hangar.get_total_overtime(), hangar.get_overtime_cost());
+        Summary::new(hangar.get_name(), hangar.get_osw().to_owned(), hangar.get_mean_utilization(), overtime)
+    }
+
+    fn new(label: &str, osw: Vec<f64>, mean_utilization: f64, (overtime_hours, overtime_cost): (f64, f64)) -> Self{
+        Summary{
+            label: String::from(label),
+            msns: osw.len(),
+            total_osw: osw.iter().sum(),
+            max_osw: osw.iter().cloned().fold(0.0, f64::max),
+            mean_utilization,
+            overtime_hours,
+            overtime_cost,
+        }
+    }
+
+    /// Mean, standard deviation, minimum and maximum of every value over the summaries.
+    fn statistics(summaries: &[Summary]) -> Vec<Summary>{
+        let msns: Vec<f64> = summaries.iter().map(|s| s.msns as f64).collect();
+        let total_osw: Vec<f64> = summaries.iter().map(|s| s.total_osw).collect();
+        let max_osw: Vec<f64> = summaries.iter().map(|s| s.max_osw).collect();
+        let mean_utilization: Vec<f64> = summaries.iter().map(|s| s.mean_utilization).collect();
+        let overtime_hours: Vec<f64> = summaries.iter().map(|s| s.overtime_hours).collect();
+        let overtime_cost: Vec<f64> = summaries.iter().map(|s| s.overtime_cost).collect();
+        let statistics: [Statistic; 4] = [("mean", mean), ("std", std_dev), ("min", min), ("max", max)];
+        statistics.iter().map(|(label, f)| Summary{
+            label: String::from(*label),
+            msns: f(&msns).round() as usize,
+            total_osw: f(&total_osw),
+            max_osw: f(&max_osw),
+            mean_utilization: f(&mean_utilization),
+            overtime_hours: f(&overtime_hours),
+            overtime_cost: f(&overtime_cost),
+        }).collect()
+    }
+}
+
+fn mean(values: &[f64]) -> f64{
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+fn std_dev(values: &[f64]) -> f64{
+    let m = mean(values);
+    mean(&values.iter().map(|v| (v - m).powi(2)).collect::<Vec<f64>>()).sqrt()
+}
+
+fn min(values: &[f64]) -> f64{
+    values.iter().cloned().fold(f64::INFINITY, f64::min)
+}
+
+fn max(values: &[f64]) -> f64{
+    values.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+}
+
+/// Writes one row per summary, labelled by the first column.
+fn write_summary_table(file_name: &str, label_name: &str, summaries: &[Summary], sink: &mut OutputSink) -> Result<(), PpeError>{
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record([label_name, "msns", "total_osw", "max_osw", "mean_utilization", "overtime_hours", "overtime_cost"])
+        .map_err(|e| PpeError::output(file_name, e))?;
+    for s in summaries{
+        wtr.write_record([s.label.clone(), s.msns.to_string(), s.total_osw.to_string(), s.max_osw.to_string(), s.mean_utilization.to_string(),
+            s.overtime_hours.to_string(), s.overtime_cost.to_string()])
+            .map_err(|e| PpeError::output(file_name, e))?;
+    }
+    let data = wtr.into_inner().map_err(|e| PpeError::output(file_name, e))?;
+    sink.write(file_name, data)
+}
+
+/// Quotes a string for JSON, escaping quotes, backslashes and control characters.
+fn json_string(value: &str) -> String{
+    let mut quoted = String::from("\"");
+    for c in value.chars(){
+        match c{
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// JSON has no NaN or infinity, they are written as null.
+fn json_number(value: f64) -> String{
+    match value.is_finite(){
+        true => value.to_string(),
+        false => String::from("null"),
+    }
+}
+
+fn print_summaries(label_name: &str, summaries: &[Summary], format: Format){
+    match format{
+        Format::Text => {
+            for s in summaries{
+                // Overtime is only worked with overtime.csv
+                let overtime = match s.overtime_hours > 0.0{
+                    true => format!(", overtime {:.2}h costing {:.2}", s.overtime_hours, s.overtime_cost),
+                    false => String::new(),
+                };
+                println!("{} {}: {} MSN, total OSW {:.2}h, max OSW {:.2}h, mean SOT utilization {:.1}%{}",
+                    label_name, s.label, s.msns, s.total_osw, s.max_osw, s.mean_utilization * 100.0, overtime);
+            }
+        }
+        Format::Csv => {
+            println!("{},msns,total_osw,max_osw,mean_utilization,overtime_hours,overtime_cost", label_name);
+            for s in summaries{
+                println!("{},{},{},{},{},{},{}", s.label, s.msns, s.total_osw, s.max_osw, s.mean_utilization, s.overtime_hours, s.overtime_cost);
+            }
+        }
+        Format::Json => {
+            let entries: Vec<String> = summaries.iter().map(|s| format!(
+                "{{{}:{},\"msns\":{},\"total_osw\":{},\"max_osw\":{},\"mean_utilization\":{},\"overtime_hours\":{},\"overtime_cost\":{}}}",
+                json_string(label_name), json_string(&s.label), s.msns, json_number(s.total_osw), json_number(s.max_osw),
+                json_number(s.mean_utilization), json_number(s.overtime_hours), json_number(s.overtime_cost))).collect();
+            println!("[{}]", entries.join(","));
+        }
+    }
+}
+
+/// Scenario file (or defaults) with the command line options applied on top.
+fn resolve_settings(options: &Options) -> Result<Settings, PpeError>{
+    let mut settings = match &options.scenario{
+        Some(path) => Settings::load(path)?,
+        None => Settings::new(),
+    };
+    if let Some(data_dir) = &options.data_dir{
+        settings.set_data_dir(data_dir);
+    }
+    if let Some(output_dir) = &options.output_dir{
+        settings.set_output_dir(output_dir);
+    }
+    // Paths from the command line are relative to the working directory
+    settings.resolve_paths(Path::new(""));
+    if let Some(production_rate) = options.production_rate{
+        settings.set_production_rate(production_rate)?;
+    }
+    if let Some(tact_time) = options.tact_time{
+        settings.set_tacttime(tact_time);
+    }
+    if let Some(inefficiency) = options.inefficiency{
+        settings.set_bc_inefficiency(inefficiency);
+    }
+    if let Some(efficiency) = options.efficiency{
+        settings.set_technical_efficiency(efficiency);
+    }
+    if let Some(efficiency) = options.shift_efficiency{
+        settings.set_shift_efficiency(efficiency);
+    }
+    if options.log_events{
+        settings.set_log_events(true);
+    }
+    if let Some(start_date) = options.start_date{
+        settings.set_start_date(start_date);
+    }
+    if let Some(rate) = options.sick_leave_rate{
+        settings.set_sick_leave_rate(rate);
+    }
+    if let Some(seed) = options.seed{
+        settings.set_seed(seed);
+    }
+    if let Some(replications) = options.replications{
+        settings.set_replications(replications);
+    }
+    if let Some(rate) = options.learning_rate{
+        settings.set_learning_rate(rate);
+    }
+    if let Some(floor) = options.learning_floor{
+        settings.set_learning_floor(floor);
+    }
+    if let Some(cost) = options.overtime_cost{
+        settings.set_overtime_cost(cost);
+    }
+    if let Some(policy) = options.lending{
+        settings.set_lending_policy(policy);
+    }
+    if let Some(efficiency) = options.lending_efficiency{
+        settings.set_lending_efficiency(efficiency);
+    }
+    if let Some(rule) = options.dispatch{
+        settings.set_dispatch(rule);
+    }
+    if let Some(policy) = options.exit_policy{
+        settings.set_exit_policy(policy);
+    }
+    if let Some(capacity) = options.exit_capacity{
+        settings.set_exit_capacity(capacity);
+    }
+    if let Some(hours) = options.transfer_hours{
+        settings.set_transfer_hours(hours);
+    }
+    if let Some(tacts) = options.max_held_tacts{
+        settings.set_max_held_tacts(tacts);
+    }
+    // Without any hangars, all hangars of the input data are added when loading
+    if !options.hangars.is_empty(){
+        settings.set_hangars(options.hangars.clone());
+    }
+    Ok(settings)
+}
+
+fn build_sim(settings: &Settings, sink: OutputSink) -> sim::SIM{
+    let mut sim = sim::SIM::from_settings(settings.clone());
+    sim.set_output_sink(sink);
+    sim
+}
+
+fn run(options: &Options) -> Result<(), PpeError>{
     let start = std::time::SystemTime::now();
-    match sim.load_data(){
-        Ok(_) => println!("{:.2}s - Data Loaded.", start.elapsed().unwrap().as_secs_f32()),
-        Err(_) => println!("0 - Error at loading Data."),
+    let settings = resolve_settings(options)?;
+    if settings.get_sick_leave().replications > 1{
+        return replicate(options, &settings);
+    }
+    let mut sim = build_sim(&settings, settings.get_output_sink());
+
+    sim.load_data()?;
+    if options.verbosity > 1{
+        println!("{:.2}s - Data Loaded.", start.elapsed().unwrap().as_secs_f32());
+    }
+    sim.run()?;
+    if options.verbosity > 1{
+        println!("{:.2}s - Calculation completed successfully.", start.elapsed().unwrap().as_secs_f32());
+    }
+    if options.verbosity > 0{
+        let mut summaries: Vec<Summary> = sim.get_hangars().iter().map(Summary::from_hangar).collect();
+        if summaries.len() > 1{
+            summaries.push(Summary::from_sim("total", &sim));
+        }
+        print_summaries("hangar", &summaries, options.format);
+    }
+    Ok(())
+}
+
+/// Runs the scenario once per seed to show the spread of the OSW under random sick leave.
+fn replicate(options: &Options, settings: &Settings) -> Result<(), PpeError>{
+    let first_seed = settings.get_sick_leave().seed;
+    let mut summaries = vec![];
+    for replication in 0..settings.get_sick_leave().replications as u64{
+        let mut case = settings.clone();
+        case.set_seed(first_seed.wrapping_add(replication));
+        // The first replication writes the usual outputs, the others only their summary
+        let sink = if replication == 0 { case.get_output_sink() } else { OutputSink::memory() };
+        let mut sim = build_sim(&case, sink);
+        sim.load_data()?;
+        sim.run()?;
+        if options.verbosity > 1{
+            println!("Finished replication {} with seed {}", replication + 1, case.get_sick_leave().seed);
+        }
+        summaries.push(Summary::from_sim(&case.get_sick_leave().seed.to_string(), &sim));
     }
-    sim.run().expect("- Error During Simulation Run.");
 
-    let time_since_start = start.elapsed().unwrap().as_secs_f32();
-    print!("{:.2}s - Calculation completed successfully.", time_since_start);
+    let mut sink = settings.get_output_sink();
+    write_summary_table("replications.csv", "seed", &summaries, &mut sink)?;
+
+    if options.verbosity > 0{
+        if options.verbosity > 1{
+            print_summaries("seed", &summaries, options.format);
+        }
+        print_summaries("replications", &Summary::statistics(&summaries), options.format);
+    }
+    Ok(())
+}
+
+fn validate(options: &Options) -> Result<(), PpeError>{
+    let settings = resolve_settings(options)?;
+    let sim = build_sim(&settings, OutputSink::memory());
+    let report = sim.validate();
+    if options.verbosity > 0 || report.has_errors(){
+        println!("{}", report);
+    }
+    if report.has_errors(){
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn sweep(options: &Options, spec: &SweepSpec) -> Result<(), PpeError>{
+    let settings = resolve_settings(options)?;
+    let mut summaries = vec![];
+    for value in &spec.values{
+        let mut case = settings.clone();
+        match spec.parameter{
+            SweepParameter::TactTime => case.set_tacttime(*value),
+            SweepParameter::Inefficiency => case.set_bc_inefficiency(*value),
+        }
+        // Single cases are kept in memory, only the sweep table is written
+        let mut sim = build_sim(&case, OutputSink::memory());
+        sim.load_data()?;
+        sim.run()?;
+        if options.verbosity > 1{
+            println!("Finished {} = {}", spec.parameter.get_name(), value);
+        }
+        summaries.push(Summary::from_sim(&value.to_string(), &sim));
+    }
+
+    let mut sink = settings.get_output_sink();
+    write_summary_table("sweep.csv", spec.parameter.get_name(), &summaries, &mut sink)?;
+    sink.write("settings.toml", settings.to_toml().into_bytes())?;
+
+    if options.verbosity > 0{
+        print_summaries(spec.parameter.get_name(), &summaries, options.format);
+    }
+    Ok(())
+}
+
+fn main() {
+    let options = match cli::parse_args(std::env::args().skip(1)){
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let result = match &options.command{
+        Command::Help => {
+            print!("{}", cli::USAGE);
+            Ok(())
+        }
+        Command::Run => run(&options),
+        Command::Validate => validate(&options),
+        Command::Sweep(spec) => sweep(&options, spec),
+    };
+
+    if let Err(e) = result{
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn json_values_are_escaped(){
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+        assert_eq!(json_number(1.5), "1.5");
+        assert_eq!(json_number(f64::NAN), "null");
+        assert_eq!(json_number(f64::NEG_INFINITY), "null");
+    }
 }
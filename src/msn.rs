@@ -1,24 +1,246 @@
+use std::collections::HashMap;
 use crate::{sot::SOT, task::Task};
+use crate::intern::{pick_mut, pick_mut_ordered, Interner};
+use crate::dispatch::Job;
+use crate::learning::Learning;
 
 #[derive(Debug, Clone)]
 pub struct MSN{
     tasks: Vec<Task>,
     msn: u32,
     version: String,
+    // Task positions by (station id, SOT id) and by SOT id, built when entering a hangar
+    station_sot_index: HashMap<(usize, usize), Vec<usize>>,
+    sot_index: HashMap<usize, Vec<usize>>,
+    // Positions of the predecessors of every task, same order as tasks
+    predecessors: Vec<Vec<usize>>,
+    successors: Vec<Vec<usize>>,
+    has_precedence: bool,
+    // Critical path of every task as of the start of the tact, see plan_critical_paths
+    critical_paths: Vec<f64>,
+    // Tact in which the MSN entered its hangar
+    entry_tact: usize,
+    // Workload relative to tasks.csv after the learning curve
+    learning_factor: f64,
 }
 
 impl MSN{
     pub fn new(msn: u32, version: &str, task_table: &Vec<Task>) -> Self{
         // Need to look for Version here then add version tasks
         //let tasks = Task::new(30.0, vec!["0".to_string(), "1".to_string(), "2".to_string()], version.to_string(), 1, 1.0, 10.0);
+        let tasks: Vec<Task> = task_table.iter().filter(|task| task.get_version() == version).cloned().collect();
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        for (i, task) in tasks.iter().enumerate(){
+            if let Some(id) = task.get_id(){
+                ids.entry(id).or_insert(i);
+            }
+        }
+        // Unknown predecessors are rejected when reading tasks.csv
+        let predecessors: Vec<Vec<usize>> = tasks.iter()
+            .map(|task| task.get_predecessors().iter().filter_map(|id| ids.get(id.as_str()).copied()).collect())
+            .collect();
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; tasks.len()];
+        for (i, positions) in predecessors.iter().enumerate(){
+            positions.iter().for_each(|&position| successors[position].push(i));
+        }
+        let has_precedence = predecessors.iter().any(|positions| !positions.is_empty());
         let msn = Self{
-            tasks: task_table.iter().filter(|task| task.get_version() == version).cloned().collect(),
+            tasks,
             msn,
             version: String::from(version),
+            station_sot_index: HashMap::new(),
+            sot_index: HashMap::new(),
+            predecessors,
+            successors,
+            has_precedence,
+            critical_paths: vec![],
+            entry_tact: 0,
+            learning_factor: 1.0,
         };
         msn
     }
 
+    /// Copy of the MSN with another MSN number.
+    pub fn with_number(&self, msn: u32) -> MSN{
+        MSN { msn, ..self.clone() }
+    }
+
+    /// Whether any task has a predicted workload.
+    pub fn has_predictions(&self) -> bool{
+        self.tasks.iter().any(|task| task.get_predicted_workload().is_some())
+    }
+
+    /// Works with the predicted workload of the tasks that have one.
+    pub fn use_predicted_workload(&mut self){
+        self.tasks.iter_mut().for_each(|task| task.use_predicted_workload());
+    }
+
+    /// Whether any work left can still be done: some unfinished task can be worked on and none of
+    /// its predecessors waits forever, directly or further up the chain.
+    pub fn can_progress(&self, workable: impl Fn(&Task) -> bool) -> bool{
+        let mut finishable: Vec<bool> = self.tasks.iter().map(|task| task.get_workload() <= 0.0).collect();
+        // Tasks in a cycle of predecessors never become finishable
+        let mut changed = true;
+        while changed{
+            changed = false;
+            for (i, task) in self.tasks.iter().enumerate(){
+                if !finishable[i] && workable(task) && self.predecessors[i].iter().all(|p| finishable[*p]){
+                    finishable[i] = true;
+                    changed = true;
+                }
+            }
+        }
+        self.tasks.iter().zip(&finishable).any(|(task, finishable)| task.get_workload() > 0.0 && *finishable)
+    }
+
+    /// Whether any task has to wait for another one.
+    pub fn has_precedence(&self) -> bool{
+        self.has_precedence
+    }
+
+    /// A task can be worked on once all its predecessors are finished.
+    fn is_available(&self, position: usize) -> bool{
+        self.predecessors[position].iter().all(|&predecessor| self.tasks[predecessor].get_workload() <= 0.0)
+    }
+
+    /// Positions of the indexed tasks that can be worked on.
+    fn get_available(&self, positions: &[usize]) -> Vec<usize>{
+        positions.iter().copied().filter(|&position| self.is_available(position)).collect()
+    }
+
+    fn get_blocked<'a>(&'a self, positions: Option<&'a Vec<usize>>) -> impl Iterator<Item = &'a Task> + 'a{
+        positions.into_iter().flatten()
+            .filter(|&&position| self.tasks[position].get_workload() > 0.0 && !self.is_available(position))
+            .map(|&position| &self.tasks[position])
+    }
+
+    /// Number of the SOT's unfinished tasks that wait for a predecessor.
+    pub fn count_blocked(&self, sot_id: usize) -> usize{
+        match self.has_precedence{
+            true => self.get_blocked(self.sot_index.get(&sot_id)).count(),
+            false => 0,
+        }
+    }
+
+    /// Workload left of every task and its longest chain of successors, same order as tasks.
+    /// Tasks in a cycle of predecessors only count their own workload.
+    fn get_critical_paths(&self) -> Vec<f64>{
+        let mut paths: Vec<f64> = self.tasks.iter().map(|task| task.get_workload()).collect();
+        // From the last tasks of every chain back to their predecessors
+        let mut open: Vec<usize> = self.successors.iter().map(|successors| successors.len()).collect();
+        let mut ready: Vec<usize> = (0..self.tasks.len()).filter(|&i| open[i] == 0).collect();
+        while let Some(i) = ready.pop(){
+            for &predecessor in &self.predecessors[i]{
+                paths[predecessor] = paths[predecessor].max(self.tasks[predecessor].get_workload() + paths[i]);
+                open[predecessor] -= 1;
+                if open[predecessor] == 0{
+                    ready.push(predecessor);
+                }
+            }
+        }
+        paths
+    }
+
+    /// Works out the critical paths of the tasks once per tact, the jobs of every SOT use them until
+    /// the next call.
+    pub fn plan_critical_paths(&mut self){
+        if self.has_precedence{
+            self.critical_paths = self.get_critical_paths();
+        }
+    }
+
+    /// Adds the unfinished tasks of the SOT the MSN offers at its station to jobs, see DispatchRule.
+    /// At a station of the SOT's assignment that is all its tasks, elsewhere those listed at the station.
+    pub fn add_jobs(&self, station_id: usize, station: &str, sot_id: usize, assignment: Option<usize>, jobs: &mut Vec<Job>){
+        let at_station = self.station_sot_index.get(&(station_id, sot_id));
+        let positions = match assignment{
+            Some(_) => self.sot_index.get(&sot_id),
+            None => at_station,
+        };
+        let positions = match positions{
+            Some(positions) => positions,
+            None => return,
+        };
+        for &position in positions{
+            let task = &self.tasks[position];
+            if task.get_workload() <= 0.0 || (self.has_precedence && !self.is_available(position)){
+                continue;
+            }
+            jobs.push(Job{
+                station_id,
+                position,
+                entry_tact: self.entry_tact,
+                workload: task.get_workload(),
+                // Without precedence every task is its own critical path
+                critical_path: self.critical_paths.get(position).copied().unwrap_or(task.get_workload()),
+                at_station: at_station.is_some_and(|at_station| at_station.binary_search(&position).is_ok()),
+                ends_here: task.is_last_station(station),
+                assignment,
+            });
+        }
+    }
+
+    /// Tasks at the given positions, in the given order. Positions must be unique.
+    pub fn pick_tasks(&mut self, positions: &[usize]) -> Vec<&mut Task>{
+        pick_mut_ordered(&mut self.tasks, positions)
+    }
+
+    /// Workload of the SOT's tasks at the station that wait for a predecessor.
+    pub fn get_blocked_workload(&self, station_id: usize, sot_id: usize) -> f64{
+        match self.has_precedence{
+            true => self.get_blocked(self.station_sot_index.get(&(station_id, sot_id))).map(|task| task.get_workload()).sum(),
+            false => 0.0,
+        }
+    }
+
+    /// Workload of all the SOT's tasks that wait for a predecessor.
+    pub fn get_blocked_sot_workload(&self, sot_id: usize) -> f64{
+        match self.has_precedence{
+            true => self.get_blocked(self.sot_index.get(&sot_id)).map(|task| task.get_workload()).sum(),
+            false => 0.0,
+        }
+    }
+
+    pub fn set_entry_tact(&mut self, tact: usize){
+        self.entry_tact = tact;
+    }
+
+    pub fn get_entry_tact(&self) -> usize{
+        self.entry_tact
+    }
+
+    pub fn get_msn(&self) -> u32{
+        self.msn
+    }
+
+    /// SOTs of the tasks completed since the last call, one entry per task, with the share of the
+    /// tact that had passed when each was done.
+    pub fn take_completed_tasks(&mut self) -> Vec<(String, f64)>{
+        self.tasks.iter_mut()
+            .filter_map(|task| task.check_completed().then(|| (task.get_sot().to_owned(), task.get_finish_share())))
+            .collect()
+    }
+
+    pub fn get_version(&self) -> &str{
+        &self.version
+    }
+
+    /// Scales every task by the learning factor of its SOT for this unit of the version.
+    pub fn apply_learning(&mut self, learning: &Learning, unit: u32){
+        let standard = self.get_workload();
+        for task in self.tasks.iter_mut(){
+            let factor = learning.get_factor(task.get_sot(), &self.version, unit);
+            task.scale_workload(factor);
+        }
+        if standard > 0.0{
+            self.learning_factor = self.get_workload() / standard;
+        }
+    }
+
+    pub fn get_learning_factor(&self) -> f64{
+        self.learning_factor
+    }
+
     fn add_task(&mut self, task: Task){
         self.tasks.push(task);
     }
@@ -32,12 +254,71 @@ impl MSN{
     }
 
     pub fn get_tasks(&mut self, station: &str, sot: &SOT) -> Vec<&mut Task>{
-        self.tasks
+        let mut tasks: Vec<&mut Task> = self.tasks
             .iter_mut()
             .filter(|task|{
                 task.get_stations().contains(&String::from(station)) && task.get_sot() == &sot.get_name()
             })
-            .collect()
+            .collect();
+        // Tasks that can't be continued at a later station come first
+        tasks.sort_by_key(|task| !task.is_last_station(station));
+        tasks
+    }
+
+    /// Indexes the tasks by the station and SOT ids of the hangar the MSN enters.
+    /// Tasks with stations or SOTs unknown to the hangar are never worked on.
+    pub fn build_index(&mut self, stations: &Interner, sots: &Interner){
+        self.station_sot_index.clear();
+        self.sot_index.clear();
+        for (i, task) in self.tasks.iter().enumerate(){
+            let sot_id = match sots.get(task.get_sot()){
+                Some(id) => id,
+                None => continue,
+            };
+            self.sot_index.entry(sot_id).or_default().push(i);
+            for station in task.get_station_refs(){
+                if let Some(station_id) = stations.get(station){
+                    let positions = self.station_sot_index.entry((station_id, sot_id)).or_default();
+                    // A station listed twice in one task must not add the task twice
+                    if positions.last() != Some(&i){
+                        positions.push(i);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Same as get_tasks, but using the index from build_index.
+    /// Tasks waiting for a predecessor are left out.
+    pub fn get_indexed_tasks(&mut self, station_id: usize, station: &str, sot_id: usize) -> Vec<&mut Task>{
+        let mut tasks = match self.station_sot_index.get(&(station_id, sot_id)){
+            Some(positions) if self.has_precedence => {
+                let available = self.get_available(positions);
+                pick_mut(&mut self.tasks, &available)
+            }
+            Some(positions) => pick_mut(&mut self.tasks, positions),
+            None => return vec![],
+        };
+        // Tasks that can't be continued at a later station come first
+        tasks.sort_by_key(|task| !task.is_last_station(station));
+        tasks
+    }
+
+    /// Same as get_sot_tasks, but using the index from build_index.
+    /// Tasks waiting for a predecessor are left out.
+    pub fn get_indexed_sot_tasks(&mut self, sot_id: usize) -> Vec<&mut Task>{
+        match self.sot_index.get(&sot_id){
+            Some(positions) if self.has_precedence => {
+                let available = self.get_available(positions);
+                pick_mut(&mut self.tasks, &available)
+            }
+            Some(positions) => pick_mut(&mut self.tasks, positions),
+            None => vec![],
+        }
+    }
+
+    pub fn get_all_tasks(&self) -> &Vec<Task>{
+        &self.tasks
     }
 
     pub fn get_sot_tasks(&mut self, sot: &SOT) -> Vec<&mut Task>{
@@ -53,4 +334,69 @@ impl MSN{
 
 #[cfg(test)]
 mod tests{
+    use super::*;
+
+    #[test]
+    fn tasks_ending_at_station_come_first(){
+        let task_table = vec![
+            Task::new(10.0, vec!["S1".to_string(), "S2".to_string()], "MECH".to_string(), "A".to_string()),
+            Task::new(20.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string()),
+        ];
+        let mut msn = MSN::new(1, "A", &task_table);
+        let sot = SOT::new("MECH", vec!["S1".to_string()], 1, 1.0);
+
+        let tasks = msn.get_tasks("S1", &sot);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].get_workload(), 20.0);
+        assert_eq!(msn.get_tasks("S2", &sot).len(), 1);
+    }
+
+    #[test]
+    fn indexed_tasks_match_filtered_tasks(){
+        let task_table = vec![
+            Task::new(10.0, vec!["S1".to_string(), "S2".to_string()], "MECH".to_string(), "A".to_string()),
+            Task::new(20.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string()),
+            Task::new(30.0, vec!["S2".to_string()], "ELEC".to_string(), "A".to_string()),
+        ];
+        let mut msn = MSN::new(1, "A", &task_table);
+        let mut stations = Interner::new();
+        stations.intern("S1");
+        stations.intern("S2");
+        let mut sots = Interner::new();
+        sots.intern("MECH");
+        sots.intern("ELEC");
+        msn.build_index(&stations, &sots);
+
+        let mech = SOT::new("MECH", vec!["S1".to_string()], 1, 1.0);
+        let filtered: Vec<f64> = msn.get_tasks("S1", &mech).iter().map(|t| t.get_workload()).collect();
+        let indexed: Vec<f64> = msn.get_indexed_tasks(0, "S1", 0).iter().map(|t| t.get_workload()).collect();
+        assert_eq!(filtered, indexed);
+        assert_eq!(msn.get_indexed_tasks(1, "S2", 1).len(), 1);
+        assert_eq!(msn.get_indexed_sot_tasks(0).len(), 2);
+    }
+
+    #[test]
+    fn successors_wait_for_predecessors(){
+        let mut first = Task::new(10.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string());
+        first.set_id(Some(String::from("T1")));
+        let mut second = Task::new(20.0, vec!["S1".to_string()], "ELEC".to_string(), "A".to_string());
+        second.set_predecessors(vec![String::from("T1")]);
+        let mut msn = MSN::new(1, "A", &vec![first, second]);
+        let mut stations = Interner::new();
+        stations.intern("S1");
+        let mut sots = Interner::new();
+        sots.intern("MECH");
+        sots.intern("ELEC");
+        msn.build_index(&stations, &sots);
+
+        assert!(msn.has_precedence());
+        assert!(msn.get_indexed_tasks(0, "S1", 1).is_empty());
+        assert_eq!(msn.count_blocked(1), 1);
+        assert_eq!(msn.get_blocked_workload(0, 1), 20.0);
+        assert_eq!(msn.get_blocked_sot_workload(1), 20.0);
+
+        msn.get_indexed_tasks(0, "S1", 0)[0].work_and_get_remaining_time(10.0, 1.0);
+        assert_eq!(msn.get_indexed_tasks(0, "S1", 1).len(), 1);
+        assert_eq!(msn.count_blocked(1), 0);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+/// Overtime a SOT works when workload due at its stations would otherwise travel, from overtime.csv.
+/// Hours are per worker, every present worker may work them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OvertimePolicy{
+    pub max_per_tact: f64,
+    // No weekly limit if None
+    pub max_per_week: Option<f64>,
+    // Workload due at the SOT's stations above which overtime is worked
+    pub threshold: f64,
+    // Cost per worker hour of overtime
+    pub cost_per_hour: f64,
+}
+
+impl OvertimePolicy{
+    /// Extra hours per worker in a tact with the given due workload,
+    /// after the hours per worker already worked in the week.
+    pub fn get_hours(&self, due_workload: f64, week_hours: f64) -> f64{
+        if due_workload <= self.threshold{
+            return 0.0;
+        }
+        let week_left = match self.max_per_week{
+            Some(max_per_week) => (max_per_week - week_hours).max(0.0),
+            None => f64::INFINITY,
+        };
+        self.max_per_tact.min(week_left).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn hours_within_tact_and_week_limits(){
+        let policy = OvertimePolicy { max_per_tact: 2.0, max_per_week: Some(5.0), threshold: 10.0, cost_per_hour: 50.0 };
+        assert_eq!(policy.get_hours(10.0, 0.0), 0.0);
+        assert_eq!(policy.get_hours(12.0, 0.0), 2.0);
+        assert_eq!(policy.get_hours(12.0, 4.0), 1.0);
+        assert_eq!(policy.get_hours(12.0, 5.0), 0.0);
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use crate::engine::{Event, EventKind, EventQueue, Schedule};
+use crate::hangar::Hangar;
+use crate::msn::MSN;
+use crate::shift::ShiftSystem;
+use crate::downtime::Downtime;
+
+/// The tact line as a schedule: one MSN enters at the start of every tact until the tact plan is
+/// done, all MSNs move on at the end of every tact, and the line runs on until the last MSN left.
+/// A tact in which the last MSN is held adds a tact without entry, the line stops if an MSN is held
+/// too long. Shift starts and ends follow
+/// the shift system in line hours, days without a calendar.
+/// A tact is worked in slices from one event to the next: shift starts and ends, absences and
+/// downtime starts and ends change the capacity from their time on. Task completions are put at the
+/// time within the slice their workload was done.
+pub struct TactSchedule<'a>{
+    hangar: &'a mut Hangar,
+    msns: VecDeque<MSN>,
+    tact_time: f64,
+    shift_system: ShiftSystem,
+    downtime: Vec<Downtime>,
+    // Line hour up to which the current tact is worked, None between tacts
+    worked_until: Option<f64>,
+    // Tacts until the last MSN left, grows with every held tact
+    tacts: usize,
+    // Next shift to start: day start, shift index and start
+    next_shift: (f64, usize, f64),
+    // Ends of the started shifts that aren't scheduled yet
+    open_shifts: VecDeque<(f64, String)>,
+}
+
+impl<'a> TactSchedule<'a>{
+    pub fn new(hangar: &'a mut Hangar, msns: Vec<MSN>, tact_time: f64, shift_system: &ShiftSystem) -> Self{
+        let tacts = msns.len() + hangar.get_number_of_stations();
+        let downtime = hangar.get_downtime().to_owned();
+        Self {
+            hangar,
+            msns: msns.into(),
+            tact_time,
+            shift_system: shift_system.clone(),
+            downtime,
+            worked_until: None,
+            tacts,
+            next_shift: (0.0, 0, 0.0),
+            open_shifts: VecDeque::new(),
+        }
+    }
+
+    /// Entry, start, end, the shifts and the downtime of a tact.
+    fn schedule_tact(&mut self, queue: &mut EventQueue, tact: usize, entry: bool){
+        let from = tact as f64 * self.tact_time;
+        let to = (tact + 1) as f64 * self.tact_time;
+        if entry{
+            if let Some(msn) = self.msns.front(){
+                queue.schedule(from, EventKind::MsnEntry(msn.get_msn()));
+            }
+        }
+        queue.schedule(from, EventKind::TactStart(tact));
+        queue.schedule(to, EventKind::TactEnd(tact));
+        self.schedule_shifts(queue, to);
+        self.schedule_downtime(queue, from, to);
+    }
+
+    /// Downtime starts and ends within the given line hours.
+    fn schedule_downtime(&self, queue: &mut EventQueue, from: f64, to: f64){
+        for downtime in &self.downtime{
+            let start = downtime.start.max(0.0);
+            let end = downtime.start + downtime.duration;
+            if end <= start{
+                continue;
+            }
+            if start >= from && start < to{
+                queue.schedule(start, EventKind::DowntimeStart { station: downtime.station.to_owned(), reason: downtime.reason.to_owned() });
+            }
+            if end > from && end <= to{
+                queue.schedule(end, EventKind::DowntimeEnd { station: downtime.station.to_owned(), reason: downtime.reason.to_owned() });
+            }
+        }
+    }
+
+    /// Works the current tact from where it was worked up to the next event, with the capacity as
+    /// it is now. The tact's end is always one of the events ahead.
+    fn work_until_next_event(&mut self, queue: &mut EventQueue){
+        let (Some(from), Some(to)) = (self.worked_until, queue.peek_time()) else{
+            return;
+        };
+        if to <= from{
+            return;
+        }
+        let hours = to - from;
+        for completion in self.hangar.work_tact(hours){
+            queue.schedule(from + completion.share.min(1.0) * hours, EventKind::TaskCompletion { msn: completion.msn, sot: completion.sot });
+        }
+        self.worked_until = Some(to);
+    }
+
+    /// Shift starts before and shift ends until the given line hour.
+    fn schedule_shifts(&mut self, queue: &mut EventQueue, until: f64){
+        let hours_per_day = self.shift_system.get_hours_per_day();
+        let shifts = self.shift_system.get_shifts();
+        if hours_per_day <= 0.0 || shifts.is_empty(){
+            return;
+        }
+        let (mut day_start, mut index, mut start) = self.next_shift;
+        while start < until{
+            let shift = &shifts[index];
+            let end = start + shift.get_net_hours();
+            queue.schedule(start, EventKind::ShiftStart(shift.name.to_owned()));
+            self.open_shifts.push_back((end, shift.name.to_owned()));
+            start = end;
+            index += 1;
+            if index == shifts.len(){
+                index = 0;
+                day_start += hours_per_day;
+                start = day_start;
+            }
+        }
+        self.next_shift = (day_start, index, start);
+        while let Some((end, name)) = self.open_shifts.pop_front(){
+            if end > until{
+                self.open_shifts.push_front((end, name));
+                break;
+            }
+            queue.schedule(end, EventKind::ShiftEnd(name));
+        }
+    }
+}
+
+impl Schedule for TactSchedule<'_>{
+    fn start(&mut self, queue: &mut EventQueue){
+        if self.tacts > 0{
+            self.schedule_tact(queue, 0, true);
+        }
+    }
+
+    fn handle(&mut self, event: &Event, queue: &mut EventQueue){
+        match &event.kind{
+            EventKind::MsnEntry(_) => {
+                if let Some(msn) = self.msns.pop_front(){
+                    self.hangar.insert_msn(msn);
+                }
+            }
+            EventKind::TactStart(_) => {
+                for worker in self.hangar.start_tact(){
+                    queue.schedule(event.time, EventKind::Absence { worker });
+                }
+                self.worked_until = Some(event.time);
+            }
+            EventKind::TactEnd(tact) => {
+                let outcome = self.hangar.end_tact();
+                self.worked_until = None;
+                for completion in outcome.completed{
+                    queue.schedule(event.time, EventKind::TaskCompletion { msn: completion.msn, sot: completion.sot });
+                }
+                if let Some(msn) = outcome.exited{
+                    queue.schedule(event.time, EventKind::MsnExit(msn));
+                }
+                if outcome.held{
+                    self.tacts += 1;
+                }
+                if tact + 1 < self.tacts && self.hangar.get_stopped_msn().is_none(){
+                    self.schedule_tact(queue, tact + 1, !outcome.held);
+                }
+                else{
+                    // The line stops, shifts end with it
+                    for (_, name) in self.open_shifts.drain(..){
+                        queue.schedule(event.time, EventKind::ShiftEnd(name));
+                    }
+                }
+            }
+            EventKind::ShiftStart(shift) => self.hangar.set_shift(shift, true),
+            EventKind::ShiftEnd(shift) => self.hangar.set_shift(shift, false),
+            EventKind::Absence { worker } => self.hangar.set_absent(worker),
+            EventKind::DowntimeStart { station, .. } => self.hangar.set_station_down(station.as_deref(), true),
+            EventKind::DowntimeEnd { station, .. } => self.hangar.set_station_down(station.as_deref(), false),
+            // Completions and exits report what the tact worked out
+            EventKind::TaskCompletion { .. } | EventKind::MsnExit(_) => {}
+        }
+        self.work_until_next_event(queue);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::engine::Engine;
+    use crate::datasource::{fixture, DataSource, InputFile};
+    use crate::capacity::CapacityFactors;
+    use crate::dispatch::Dispatch;
+    use crate::unfinished::{ExitPolicy, UnfinishedWork};
+    use crate::util;
+
+    fn hangar() -> Hangar{
+        let mut hangar = Hangar::new("1", 7.0);
+        hangar.set_stations(util::get_stations(&fixture(), "1").unwrap());
+        hangar.set_sot(util::load_sots(&fixture(), "1", &CapacityFactors::new(0.8), Dispatch::default(), &ShiftSystem::default()).unwrap());
+        hangar
+    }
+
+    #[test]
+    fn schedule_matches_tact_loop(){
+        let msns = util::get_tactplan(&fixture(), "1").unwrap();
+        let mut looped = hangar();
+        for msn in msns.clone(){
+            looped.insert_msn(msn);
+            looped.tact();
+        }
+        for _ in 0..looped.get_number_of_stations(){
+            looped.tact();
+        }
+
+        let mut scheduled = hangar();
+        let mut engine = Engine::new(true);
+        engine.run(&mut TactSchedule::new(&mut scheduled, msns, 7.0, &ShiftSystem::default()));
+        assert_eq!(scheduled.get_osw(), looped.get_osw());
+
+        let log = engine.get_log().unwrap();
+        let exits: Vec<(f64, String)> = log.iter()
+            .filter(|event| event.kind.get_name() == "msn_exit")
+            .map(|event| (event.time, event.kind.get_detail()))
+            .collect();
+        assert_eq!(exits, vec![(28.0, String::from("1")), (35.0, String::from("2")), (42.0, String::from("3"))]);
+        // Two shifts of 7 net hours a day
+        assert!(log.iter().any(|event| event.time == 7.0 && event.kind == EventKind::ShiftStart(String::from("late"))));
+        assert!(log.iter().any(|event| matches!(event.kind, EventKind::TaskCompletion { .. })));
+    }
+
+    #[test]
+    fn tasks_complete_when_their_workload_is_done(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload\nS1,MECH,A,2\nS1,MECH,A,5\nS1,MECH,A,8\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+        let mut hangar = Hangar::new("1", 10.0);
+        hangar.set_stations(util::get_stations(&source, "1").unwrap());
+        hangar.set_sot(util::load_sots(&source, "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()).unwrap());
+        let mut engine = Engine::new(true);
+        engine.run(&mut TactSchedule::new(&mut hangar, util::get_tactplan(&source, "1").unwrap(), 10.0, &ShiftSystem::default()));
+
+        // 10 productive hours per tact: 2 and 5 hours into the first tact, the last task is open work
+        let completions: Vec<f64> = engine.get_log().unwrap().iter()
+            .filter(|event| matches!(event.kind, EventKind::TaskCompletion { .. }))
+            .map(|event| event.time)
+            .collect();
+        assert_eq!(completions.len(), 2);
+        assert!((completions[0] - 2.0).abs() < 1e-9 && (completions[1] - 7.0).abs() < 1e-9, "{:?}", completions);
+    }
+
+    fn run_one_day(source: &DataSource, downtime: Vec<Downtime>) -> (Hangar, Vec<Event>){
+        let mut hangar = Hangar::new("1", 14.0);
+        hangar.set_stations(util::get_stations(source, "1").unwrap());
+        hangar.set_sot(util::load_sots(source, "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()).unwrap());
+        hangar.set_downtime(downtime);
+        let mut engine = Engine::new(true);
+        engine.run(&mut TactSchedule::new(&mut hangar, util::get_tactplan(source, "1").unwrap(), 14.0, &ShiftSystem::default()));
+        let log = engine.get_log().unwrap().to_owned();
+        (hangar, log)
+    }
+
+    #[test]
+    fn shift_change_within_the_tact_changes_capacity(){
+        // A tact is one day of an early and a late shift of 7 hours; MECH works late, ELEC early
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations,shifts\nMECH,1,1.0,S1,late\nELEC,1,1.0,S1,early\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload,id,predecessors\nS1,MECH,A,7,T1,\nS1,ELEC,A,7,T2,T1\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+        let (hangar, log) = run_one_day(&source, vec![]);
+
+        // MECH finishes T1 at the end of the late shift, ELEC's shift is over by then
+        assert_eq!(hangar.get_osw(), &vec![7.0]);
+        let completions: Vec<(f64, String)> = log.iter()
+            .filter(|event| matches!(event.kind, EventKind::TaskCompletion { .. }))
+            .map(|event| (event.time, event.kind.get_detail()))
+            .collect();
+        assert_eq!(completions, vec![(14.0, String::from("1 MECH"))]);
+    }
+
+    #[test]
+    fn downtime_stops_work_from_its_start(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload\nS1,MECH,A,3\nS1,MECH,A,4\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+        let crane = Downtime { station: Some(String::from("S1")), start: 2.0, duration: 5.0, reason: String::from("crane") };
+        let (hangar, log) = run_one_day(&source, vec![crane]);
+
+        // 2 hours of the first task before the crane breaks, the rest once it is fixed at hour 7
+        let times: Vec<(f64, &str)> = log.iter()
+            .filter(|event| matches!(event.kind, EventKind::TaskCompletion { .. } | EventKind::DowntimeStart { .. } | EventKind::DowntimeEnd { .. }))
+            .map(|event| (event.time, event.kind.get_name()))
+            .collect();
+        assert_eq!(times, vec![(2.0, "downtime_start"), (7.0, "downtime_end"), (8.0, "task_completion"), (12.0, "task_completion")]);
+        assert_eq!(hangar.get_osw(), &vec![0.0]);
+        assert!(log.iter().any(|event| event.kind.get_detail() == "S1 crane"));
+    }
+
+    #[test]
+    fn hold_lets_go_at_a_station_down_for_good(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\nS2\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1;S2\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload\nS2,MECH,A,25\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+        let mut hangar = Hangar::new("1", 10.0);
+        hangar.set_stations(util::get_stations(&source, "1").unwrap());
+        hangar.set_sot(util::load_sots(&source, "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()).unwrap());
+        hangar.set_unfinished_work(UnfinishedWork { policy: ExitPolicy::Hold, ..UnfinishedWork::default() });
+        hangar.set_downtime(vec![Downtime { station: Some(String::from("S2")), start: 0.0, duration: f64::INFINITY, reason: String::from("crane") }]);
+        let mut engine = Engine::new(false);
+        engine.run(&mut TactSchedule::new(&mut hangar, util::get_tactplan(&source, "1").unwrap(), 10.0, &ShiftSystem::default()));
+
+        // MECH works 10 hours on the task while the MSN is at S1, nobody can ever work at S2 and
+        // the MSN leaves on time with the rest
+        let exits: Vec<f64> = hangar.get_deliveries().iter().map(|delivery| delivery.exit).collect();
+        assert_eq!(exits, vec![20.0]);
+        assert_eq!(hangar.get_osw(), &vec![15.0]);
+        assert_eq!(hangar.get_stopped_msn(), None);
+    }
+}
@@ -1,22 +1,325 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::datasource::{DataSource, InputFile, OutputSink};
+use crate::error::PpeError;
+use crate::shift::ShiftSystem;
+use crate::calendar::Calendar;
+use crate::lending::{Lending, LendingPolicy};
+use crate::learning::LearningModel;
+use crate::capacity::CapacityFactors;
+use crate::dispatch::Dispatch;
+use crate::unfinished::{ExitPolicy, UnfinishedWork};
+use chrono::{NaiveDate, Weekday};
+
+/// Scenario settings, can be loaded from and saved to a TOML file:
+///
+/// ```toml
+/// production_rate = 7.5
+/// bc_inefficiency = 0.086
+/// cvat = 0.8
+/// technical_efficiency = 0.95
+/// shift_efficiency = 0.97
+/// dispatch = "critical_path"
+/// hangars = ["8"]
+///
+/// [shift]
+/// working_days_per_week = 5
+/// shifts = [
+///     { name = "early", hours = 7.5, break_hours = 0.5 },
+///     { name = "late", hours = 7.5, break_hours = 0.5 },
+/// ]
+///
+/// [hangar_efficiency]
+/// "8" = 0.92
+///
+/// [calendar]
+/// start_date = "2026-01-05"
+///
+/// [sick_leave]
+/// rate = 0.086
+/// seed = 42
+/// replications = 20
+///
+/// [learning]
+/// model = "wright"
+/// rate = 0.85
+/// floor = 0.6
+///
+/// [overtime]
+/// cost_per_hour = 65.0
+///
+/// [lending]
+/// policy = "adjacent"
+/// efficiency = 0.8
+///
+/// [unfinished_work]
+/// policy = "rework"
+/// capacity = 120.0
+/// max_held_tacts = 1000
+///
+/// [inputs]
+/// data_dir = "data"
+/// tasks = "data/tasks_2025.csv"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings{
-    tacttime: f64,
+    // Overrides the tact time derived from shift system and production rate
+    #[serde(rename = "tact_time", skip_serializing_if = "Option::is_none")]
+    tacttime: Option<f64>,
+    // MSN per week
+    production_rate: f64,
+    // Absence rate of the BC, capacity is scaled by 1 - bc_inefficiency
     bc_inefficiency: f64,
+    // cvat of SOTs without one in sot.csv
+    cvat: f64,
+    // Productivity multiplier of all hangars not listed in hangar_efficiency
+    technical_efficiency: f64,
+    // Share of the shift left after handovers, briefings and the like
+    shift_efficiency: f64,
+    // Dispatch rule of SOTs without one in sot.csv
+    dispatch: Dispatch,
+    // Writes events_<hangar>.csv with every event of the run
+    log_events: bool,
+    // Hangars to simulate, all hangars of the input data if empty
+    hangars: Vec<String>,
+    shift: ShiftSystem,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    hangar_efficiency: BTreeMap<String, f64>,
+    calendar: CalendarSettings,
+    sick_leave: SickLeaveSettings,
+    learning: LearningSettings,
+    overtime: OvertimeSettings,
+    lending: Lending,
+    // What happens to OSW at line exit
+    unfinished_work: UnfinishedWork,
+    inputs: InputSettings,
+}
+
+/// Absences are sampled per tact if a rate is set or sickleave.csv exists.
+/// bc_inefficiency is then not applied, as the absence it stands for is simulated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SickLeaveSettings{
+    // Rate for SOTs and months not listed in sickleave.csv
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f64>,
+    pub seed: u64,
+    // Runs with seeds seed, seed + 1, ... to show the spread of the OSW
+    pub replications: u32,
+}
+
+impl Default for SickLeaveSettings{
+    fn default() -> Self{
+        Self { rate: None, seed: 1, replications: 1 }
+    }
+}
+
+/// Learning is applied if a rate is set or learning.csv exists, see Learning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LearningSettings{
+    pub model: LearningModel,
+    // Rate for SOTs and versions not listed in learning.csv
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f64>,
+    // Unit time relative to the first unit at which learning stops
+    pub floor: f64,
+}
+
+impl Default for LearningSettings{
+    fn default() -> Self{
+        Self { model: LearningModel::Wright, rate: None, floor: 0.5 }
+    }
+}
+
+/// Overtime is worked by the SOTs listed in overtime.csv.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OvertimeSettings{
+    // Cost per worker hour for rows of overtime.csv without a cost
+    pub cost_per_hour: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings{
+    // Tacts are not mapped to dates without a start date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<NaiveDate>,
+    // Monday onwards for the shift system's working days per week if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_days: Option<Vec<Weekday>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputSettings{
+    pub data_dir: PathBuf,
+    // Output goes to the data folder if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<PathBuf>,
+    // Explicit file paths, overriding the default names in data_dir
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tactplan: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sot: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stations: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exceptions: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workers: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sickleave: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holidays: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub learning: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overtime: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staffing: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downtime: Option<PathBuf>,
+}
+
+impl Default for InputSettings{
+    fn default() -> Self{
+        Self {
+            data_dir: PathBuf::from("data"),
+            output_dir: None,
+            tasks: None,
+            tactplan: None,
+            sot: None,
+            stations: None,
+            exceptions: None,
+            workers: None,
+            sickleave: None,
+            holidays: None,
+            learning: None,
+            overtime: None,
+            staffing: None,
+            downtime: None,
+        }
+    }
+}
+
+impl InputSettings{
+    fn get_file(&self, file: InputFile) -> Option<&PathBuf>{
+        match file{
+            InputFile::Tasks => self.tasks.as_ref(),
+            InputFile::TactPlan => self.tactplan.as_ref(),
+            InputFile::Sot => self.sot.as_ref(),
+            InputFile::Stations => self.stations.as_ref(),
+            InputFile::Exceptions => self.exceptions.as_ref(),
+            InputFile::Workers => self.workers.as_ref(),
+            InputFile::SickLeave => self.sickleave.as_ref(),
+            InputFile::Holidays => self.holidays.as_ref(),
+            InputFile::Learning => self.learning.as_ref(),
+            InputFile::Overtime => self.overtime.as_ref(),
+            InputFile::Staffing => self.staffing.as_ref(),
+            InputFile::Downtime => self.downtime.as_ref(),
+        }
+    }
+
+    /// Makes relative paths absolute, relative to the given folder.
+    fn resolve(&mut self, base: &Path){
+        let base = std::path::absolute(Path::new(".").join(base)).unwrap_or_else(|_| base.to_path_buf());
+        let paths = [&mut self.output_dir, &mut self.tasks, &mut self.tactplan, &mut self.sot, &mut self.stations, &mut self.exceptions,
+            &mut self.workers, &mut self.sickleave, &mut self.holidays, &mut self.learning, &mut self.overtime, &mut self.staffing, &mut self.downtime];
+        for path in paths.into_iter().flatten().chain([&mut self.data_dir]){
+            if path.is_relative(){
+                *path = base.join(&*path);
+            }
+        }
+    }
+}
+
+impl Default for Settings{
+    fn default() -> Self{
+        Self::new()
+    }
 }
 
 impl Settings{
     pub fn new() -> Self{
         Self {
-            tacttime: 9.3,
-            bc_inefficiency: 0.086
+            tacttime: None,
+            production_rate: 7.5,
+            bc_inefficiency: 0.086,
+            cvat: 1.0,
+            technical_efficiency: 1.0,
+            shift_efficiency: 1.0,
+            dispatch: Dispatch::Downstream,
+            log_events: false,
+            hangars: vec![],
+            shift: ShiftSystem::default(),
+            hangar_efficiency: BTreeMap::new(),
+            calendar: CalendarSettings::default(),
+            sick_leave: SickLeaveSettings::default(),
+            learning: LearningSettings::default(),
+            overtime: OvertimeSettings::default(),
+            lending: Lending::default(),
+            unfinished_work: UnfinishedWork::default(),
+            inputs: InputSettings::default(),
         }
     }
 
+    /// Reads a scenario file. Relative input and output paths in it are relative to its folder.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PpeError>{
+        let name = path.as_ref().display().to_string();
+        let text = std::fs::read_to_string(&path).map_err(|e| PpeError::missing_file(&name, e))?;
+        let mut settings = Self::from_toml(&name, &text)?;
+        settings.resolve_paths(path.as_ref().parent().unwrap_or(Path::new("")));
+        Ok(settings)
+    }
+
+    /// Makes relative input and output paths absolute, relative to the given folder, so the
+    /// settings.toml written with the results can be used from anywhere.
+    pub fn resolve_paths(&mut self, base: &Path){
+        self.inputs.resolve(base);
+    }
+
+    pub fn from_toml(name: &str, text: &str) -> Result<Self, PpeError>{
+        let settings: Settings = toml::from_str(text).map_err(|e| PpeError::BadSettings { file: String::from(name), message: e.to_string() })?;
+        check_production_rate(name, settings.production_rate)?;
+        Ok(settings)
+    }
+
+    pub fn to_toml(&self) -> String{
+        toml::to_string_pretty(self).expect("Settings always serialize to TOML")
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PpeError>{
+        let name = path.as_ref().display().to_string();
+        std::fs::write(&path, self.to_toml()).map_err(|e| PpeError::output(&name, e))
+    }
+
     pub fn set_tacttime(&mut self, tacttime: f64){
-        self.tacttime = tacttime;
+        self.tacttime = Some(tacttime);
     }
-    
+
+    /// Explicit tact time if set, otherwise derived from shift system and production rate.
     pub fn get_tacttime(&self) -> f64{
-        self.tacttime
+        match self.tacttime{
+            Some(tacttime) => tacttime,
+            None => self.shift.get_tact_time(self.production_rate),
+        }
+    }
+
+    /// Sets the MSNs per week the tact time is derived from, which has to be above 0.
+    pub fn set_production_rate(&mut self, production_rate: f64) -> Result<(), PpeError>{
+        check_production_rate("settings", production_rate)?;
+        self.production_rate = production_rate;
+        Ok(())
+    }
+
+    pub fn get_production_rate(&self) -> f64{
+        self.production_rate
     }
 
     pub fn set_bc_inefficiency(&mut self, bc_inefficiency: f64){
@@ -26,4 +329,277 @@ impl Settings{
     pub fn get_bc_inefficiency(&self) -> f64{
         self.bc_inefficiency
     }
+
+    pub fn set_technical_efficiency(&mut self, technical_efficiency: f64){
+        self.technical_efficiency = technical_efficiency;
+    }
+
+    /// Technical efficiency of the hangar, the one of all hangars if it has none of its own.
+    pub fn get_hangar_efficiency(&self, hangar: &str) -> f64{
+        self.hangar_efficiency.get(hangar).copied().unwrap_or(self.technical_efficiency)
+    }
+
+    pub fn set_cvat(&mut self, cvat: f64){
+        self.cvat = cvat;
+    }
+
+    pub fn set_shift_efficiency(&mut self, shift_efficiency: f64){
+        self.shift_efficiency = shift_efficiency;
+    }
+
+    /// Capacity factors of the SOTs of the hangar, sot.csv may override them per SOT.
+    pub fn get_capacity_factors(&self, hangar: &str) -> CapacityFactors{
+        CapacityFactors {
+            presence: 1.0 - self.bc_inefficiency,
+            cvat: self.cvat,
+            hangar_efficiency: self.get_hangar_efficiency(hangar),
+            shift_efficiency: self.shift_efficiency,
+        }
+    }
+
+    pub fn set_dispatch(&mut self, dispatch: Dispatch){
+        self.dispatch = dispatch;
+    }
+
+    pub fn get_dispatch(&self) -> Dispatch{
+        self.dispatch
+    }
+
+    pub fn set_log_events(&mut self, log_events: bool){
+        self.log_events = log_events;
+    }
+
+    pub fn get_log_events(&self) -> bool{
+        self.log_events
+    }
+
+    pub fn set_hangars(&mut self, hangars: Vec<String>){
+        self.hangars = hangars;
+    }
+
+    pub fn get_hangars(&self) -> &Vec<String>{
+        &self.hangars
+    }
+
+    pub fn set_shift_system(&mut self, shift: ShiftSystem){
+        self.shift = shift;
+    }
+
+    pub fn get_shift_system(&self) -> &ShiftSystem{
+        &self.shift
+    }
+
+    pub fn set_start_date(&mut self, start_date: NaiveDate){
+        self.calendar.start_date = Some(start_date);
+    }
+
+    /// Calendar without exception days, None if no start date is set.
+    pub fn get_calendar(&self) -> Option<Calendar>{
+        let start_date = self.calendar.start_date?;
+        let working_days = match &self.calendar.working_days{
+            Some(days) => days.clone(),
+            None => Calendar::default_working_days(self.shift.get_working_days_per_week()),
+        };
+        Some(Calendar::new(start_date, &working_days, self.shift.get_hours_per_day()))
+    }
+
+    pub fn set_sick_leave_rate(&mut self, rate: f64){
+        self.sick_leave.rate = Some(rate);
+    }
+
+    pub fn set_seed(&mut self, seed: u64){
+        self.sick_leave.seed = seed;
+    }
+
+    pub fn set_replications(&mut self, replications: u32){
+        self.sick_leave.replications = replications;
+    }
+
+    pub fn get_sick_leave(&self) -> &SickLeaveSettings{
+        &self.sick_leave
+    }
+
+    pub fn set_learning_rate(&mut self, rate: f64){
+        self.learning.rate = Some(rate);
+    }
+
+    pub fn set_learning_floor(&mut self, floor: f64){
+        self.learning.floor = floor;
+    }
+
+    pub fn get_learning(&self) -> &LearningSettings{
+        &self.learning
+    }
+
+    pub fn set_overtime_cost(&mut self, cost_per_hour: f64){
+        self.overtime.cost_per_hour = cost_per_hour;
+    }
+
+    pub fn get_overtime(&self) -> &OvertimeSettings{
+        &self.overtime
+    }
+
+    pub fn set_lending_policy(&mut self, policy: LendingPolicy){
+        self.lending.policy = policy;
+    }
+
+    pub fn set_lending_efficiency(&mut self, efficiency: f64){
+        self.lending.efficiency = efficiency;
+    }
+
+    pub fn get_lending(&self) -> &Lending{
+        &self.lending
+    }
+
+    pub fn set_exit_policy(&mut self, policy: ExitPolicy){
+        self.unfinished_work.policy = policy;
+    }
+
+    pub fn set_exit_capacity(&mut self, capacity: f64){
+        self.unfinished_work.capacity = capacity;
+    }
+
+    pub fn set_transfer_hours(&mut self, transfer_hours: f64){
+        self.unfinished_work.transfer_hours = transfer_hours;
+    }
+
+    pub fn set_max_held_tacts(&mut self, max_held_tacts: usize){
+        self.unfinished_work.max_held_tacts = max_held_tacts;
+    }
+
+    pub fn get_unfinished_work(&self) -> &UnfinishedWork{
+        &self.unfinished_work
+    }
+
+    pub fn get_inputs(&self) -> &InputSettings{
+        &self.inputs
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: impl AsRef<Path>){
+        self.inputs.data_dir = data_dir.as_ref().to_path_buf();
+    }
+
+    pub fn set_output_dir(&mut self, output_dir: impl AsRef<Path>){
+        self.inputs.output_dir = Some(output_dir.as_ref().to_path_buf());
+    }
+
+    pub fn get_output_dir(&self) -> PathBuf{
+        self.inputs.output_dir.clone().unwrap_or(self.inputs.data_dir.clone())
+    }
+
+    /// Input files from the data folder, with explicitly set files taking precedence.
+    pub fn get_data_source(&self) -> DataSource{
+        let files = [InputFile::Tasks, InputFile::TactPlan, InputFile::Sot, InputFile::Stations, InputFile::Exceptions, InputFile::Workers, InputFile::SickLeave, InputFile::Holidays, InputFile::Learning, InputFile::Overtime, InputFile::Staffing, InputFile::Downtime];
+        if files.iter().all(|file| self.inputs.get_file(*file).is_none()){
+            return DataSource::directory(&self.inputs.data_dir);
+        }
+        files.iter().fold(DataSource::files(), |source, file| {
+            let path = match self.inputs.get_file(*file){
+                Some(path) => path.clone(),
+                None => self.inputs.data_dir.join(file.file_name()),
+            };
+            source.with_file(*file, path)
+        })
+    }
+
+    pub fn get_output_sink(&self) -> OutputSink{
+        OutputSink::directory(self.get_output_dir())
+    }
+}
+
+// The tact time is the weekly hours divided by the production rate
+fn check_production_rate(file: &str, production_rate: f64) -> Result<(), PpeError>{
+    match production_rate > 0.0 && production_rate.is_finite(){
+        true => Ok(()),
+        false => Err(PpeError::BadSettings { file: String::from(file), message: format!("production_rate {} is not above 0", production_rate) }),
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn partial_file_keeps_defaults(){
+        let settings = Settings::from_toml("test.toml", "tact_time = 8.0\n[inputs]\ntasks = \"plan/tasks.csv\"\n").unwrap();
+
+        assert_eq!(settings.get_tacttime(), 8.0);
+        assert_eq!(settings.get_bc_inefficiency(), 0.086);
+        assert_eq!(settings.get_shift_system().get_shifts().len(), 2);
+        assert_eq!(settings.get_data_source().describe(InputFile::Tasks), "plan/tasks.csv");
+        assert_eq!(settings.get_data_source().describe(InputFile::Sot), PathBuf::from("data").join("sot.csv").display().to_string());
+    }
+
+    #[test]
+    fn toml_round_trip(){
+        let mut settings = Settings::new();
+        settings.set_hangars(vec![String::from("8"), String::from("9")]);
+        settings.set_output_dir("out");
+
+        let loaded = Settings::from_toml("test.toml", &settings.to_toml()).unwrap();
+        assert_eq!(loaded.get_hangars(), settings.get_hangars());
+        assert_eq!(loaded.get_output_dir(), PathBuf::from("out"));
+    }
+
+    #[test]
+    fn tact_time_derived_from_shifts(){
+        let settings = Settings::from_toml("test.toml", "production_rate = 5.0\n[shift]\nworking_days_per_week = 5\nshifts = [{ name = \"day\", hours = 8.0, break_hours = 0.5 }]\n").unwrap();
+        assert_eq!(settings.get_tacttime(), 7.5);
+        assert!((Settings::new().get_tacttime() - 9.333).abs() < 0.001);
+    }
+
+    #[test]
+    fn hangar_efficiency_overrides_technical_efficiency(){
+        let settings = Settings::from_toml("test.toml", "technical_efficiency = 0.95\n[hangar_efficiency]\n\"8\" = 0.9\n").unwrap();
+        assert_eq!(settings.get_hangar_efficiency("8"), 0.9);
+        assert_eq!(settings.get_hangar_efficiency("9"), 0.95);
+        let round_trip = Settings::from_toml("test.toml", &settings.to_toml()).unwrap();
+        assert_eq!(round_trip.get_hangar_efficiency("8"), 0.9);
+    }
+
+    #[test]
+    fn calendar_from_start_date(){
+        assert!(Settings::new().get_calendar().is_none());
+        let settings = Settings::from_toml("test.toml", "[calendar]\nstart_date = \"2026-01-05\"\nworking_days = [\"Mon\", \"Tue\"]\n").unwrap();
+        let calendar = settings.get_calendar().unwrap();
+        assert_eq!(calendar.get_working_day(2), NaiveDate::from_ymd_opt(2026, 1, 12).unwrap());
+    }
+
+    #[test]
+    fn unfinished_work_from_toml(){
+        assert_eq!(Settings::new().get_unfinished_work().policy, ExitPolicy::Sink);
+        let settings = Settings::from_toml("test.toml", "[unfinished_work]\npolicy = \"delivery_centre\"\ncapacity = 50.0\n").unwrap();
+        assert_eq!(settings.get_unfinished_work().policy, ExitPolicy::DeliveryCentre);
+        assert_eq!(settings.get_unfinished_work().capacity, 50.0);
+        assert_eq!(settings.get_unfinished_work().transfer_hours, 0.0);
+    }
+
+    #[test]
+    fn unknown_type_is_an_error(){
+        assert!(matches!(Settings::from_toml("test.toml", "tact_time = \"long\""), Err(PpeError::BadSettings { .. })));
+    }
+
+    #[test]
+    fn scenario_paths_are_relative_to_the_scenario(){
+        let dir = std::env::temp_dir().join(format!("ppe-scenario-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scenario.toml");
+        std::fs::write(&path, "[inputs]\ndata_dir = \"data\"\noutput_dir = \"/tmp/out\"\ntasks = \"plans/tasks.csv\"\n").unwrap();
+        let settings = Settings::load(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let inputs = settings.unwrap().inputs;
+        assert_eq!(inputs.data_dir, dir.join("data"));
+        assert_eq!(inputs.output_dir, Some(PathBuf::from("/tmp/out")));
+        assert_eq!(inputs.tasks, Some(dir.join("plans/tasks.csv")));
+        assert_eq!(inputs.sot, None);
+    }
+
+    #[test]
+    fn production_rate_must_be_positive(){
+        assert!(matches!(Settings::from_toml("test.toml", "production_rate = 0.0"), Err(PpeError::BadSettings { .. })));
+        let mut settings = Settings::new();
+        assert!(settings.set_production_rate(-1.0).is_err());
+        assert_eq!(settings.get_production_rate(), 7.5);
+    }
 }
\ No newline at end of file
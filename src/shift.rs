@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shift{
+    pub name: String,
+    // Paid hours of the shift, including breaks
+    pub hours: f64,
+    #[serde(default)]
+    pub break_hours: f64,
+}
+
+impl Shift{
+    pub fn new(name: &str, hours: f64, break_hours: f64) -> Self{
+        Self { name: String::from(name), hours, break_hours }
+    }
+
+    pub fn get_net_hours(&self) -> f64{
+        (self.hours - self.break_hours).max(0.0)
+    }
+}
+
+/// Shifts worked on the line. The line runs during all shifts, a SOT only during its assigned ones.
+///
+/// With a production rate in MSN per week, the tact time is the line's net hours per week divided
+/// by the rate, e.g. 2 shifts of 7.5h with 0.5h break on 5 days at rate 7.5: 2 * 7 * 5 / 7.5 = 9.333h.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShiftSystem{
+    shifts: Vec<Shift>,
+    working_days_per_week: f64,
+}
+
+impl Default for ShiftSystem{
+    fn default() -> Self{
+        // R7.5 in 2 shifts
+        ShiftSystem::new(vec![
+            Shift::new("early", 7.5, 0.5),
+            Shift::new("late", 7.5, 0.5),
+        ], 5.0)
+    }
+}
+
+impl ShiftSystem{
+    pub fn new(shifts: Vec<Shift>, working_days_per_week: f64) -> Self{
+        Self { shifts, working_days_per_week }
+    }
+
+    /// Equal shifts named "1", "2", ...
+    pub fn uniform(shifts_per_day: u8, hours_per_shift: f64, break_hours: f64, working_days_per_week: f64) -> Self{
+        let shifts = (1..=shifts_per_day)
+            .map(|i| Shift::new(&i.to_string(), hours_per_shift, break_hours))
+            .collect();
+        Self::new(shifts, working_days_per_week)
+    }
+
+    pub fn get_shifts(&self) -> &Vec<Shift>{
+        &self.shifts
+    }
+
+    pub fn get_shift(&self, name: &str) -> Option<&Shift>{
+        self.shifts.iter().find(|shift| shift.name == name)
+    }
+
+    pub fn get_working_days_per_week(&self) -> f64{
+        self.working_days_per_week
+    }
+
+    /// Net hours the line runs per working day
+    pub fn get_hours_per_day(&self) -> f64{
+        self.shifts.iter().map(|shift| shift.get_net_hours()).sum()
+    }
+
+    pub fn get_hours_per_week(&self) -> f64{
+        self.get_hours_per_day() * self.working_days_per_week
+    }
+
+    pub fn get_tact_time(&self, production_rate: f64) -> f64{
+        self.get_hours_per_week() / production_rate
+    }
+
+    /// Part of the line's hours a SOT working the given shifts is present. All shifts if empty.
+    pub fn get_share(&self, shift_names: &[String]) -> Result<f64, String>{
+        if shift_names.is_empty() || self.get_hours_per_day() <= 0.0{
+            return Ok(1.0);
+        }
+        let mut hours = 0.0;
+        for name in shift_names{
+            match self.get_shift(name){
+                Some(shift) => hours += shift.get_net_hours(),
+                None => return Err(name.to_owned()),
+            }
+        }
+        Ok(hours / self.get_hours_per_day())
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn default_matches_r7_5_in_two_shifts(){
+        let shifts = ShiftSystem::default();
+        assert!((shifts.get_tact_time(7.5) - 9.333).abs() < 0.001);
+    }
+
+    #[test]
+    fn share_of_single_shift(){
+        let shifts = ShiftSystem::new(vec![
+            Shift::new("early", 8.0, 0.5),
+            Shift::new("late", 8.0, 0.5),
+            Shift::new("night", 8.0, 0.5),
+        ], 5.0);
+        assert_eq!(shifts.get_share(&[]), Ok(1.0));
+        assert!((shifts.get_share(&[String::from("night")]).unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(shifts.get_share(&[String::from("weekend")]), Err(String::from("weekend")));
+    }
+}
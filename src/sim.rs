@@ -2,20 +2,58 @@ use crate::hangar::Hangar;
 use crate::msn::MSN;
 use crate::util;
 use crate::settings::Settings;
+use crate::error::PpeError;
+use crate::datasource::{DataSource, OutputSink};
+use crate::validate::{self, ValidationReport};
+use crate::capacity::CapacityFactors;
+use crate::engine::Engine;
+use crate::schedule::TactSchedule;
+use crate::comparison;
 
 pub struct SIM{
     hangar: Vec<Hangar>,
-    msns: Vec<MSN>,
-    settings: Settings
+    // Tact plan per hangar, same order as hangar
+    msns: Vec<Vec<MSN>>,
+    settings: Settings,
+    source: DataSource,
+    sink: OutputSink,
 }
 
 impl SIM{
     pub fn new() -> SIM{
+        SIM::with_io(DataSource::directory("data"), OutputSink::directory("data"))
+    }
+
+    pub fn with_io(source: DataSource, sink: OutputSink) -> SIM{
         SIM{
             hangar: vec![],
             msns: vec![],
-            settings: Settings::new()
+            settings: Settings::new(),
+            source,
+            sink,
+        }
+    }
+
+    /// Takes tact time, inefficiency, hangars and input/output locations from the settings.
+    pub fn from_settings(settings: Settings) -> SIM{
+        let mut sim = SIM::with_io(settings.get_data_source(), settings.get_output_sink());
+        for hangar in settings.get_hangars(){
+            sim.hangar.push(Hangar::new(hangar, settings.get_tacttime()));
         }
+        sim.settings = settings;
+        sim
+    }
+
+    pub fn get_settings(&self) -> &Settings{
+        &self.settings
+    }
+
+    pub fn set_output_sink(&mut self, sink: OutputSink){
+        self.sink = sink;
+    }
+
+    pub fn get_output_sink(&self) -> &OutputSink{
+        &self.sink
     }
 
     pub fn set_tacttime(&mut self, tact_time: f64){
@@ -41,38 +79,175 @@ impl SIM{
         &mut self.hangar[index]
     }
 
-    pub fn load_data(&mut self) -> Result<(), String>{  
-        // Add Stations from CSV to Hangar
-        let inefficiency = self.settings.get_bc_inefficiency();
-        let hangar = self.get_hangar_mut(0);
-        hangar.set_stations(util::get_stations());
-        hangar.set_sot(util::load_sots(inefficiency));
-        
-        // Set MSNs to TactPlan
-        self.msns = util::get_tactplan();
-        Ok(())
+    pub fn get_hangars(&self) -> &Vec<Hangar>{
+        &self.hangar
     }
 
-    pub fn run(&mut self) -> Result<(), String>{
-        // Run Simulation
-        let mut msns = std::mem::take(&mut self.msns);
-        let hangar = self.get_hangar_mut(0);
-        
-        //Tact until all msn are in
-        for msn in msns.drain(..){
-           hangar.insert_msn(msn);
-           hangar.tact();
+    /// Exit OSW of every MSN, in the order the MSNs left the hangar.
+    pub fn get_osw(&self) -> Vec<f64>{
+        self.hangar.iter().flat_map(|hangar| hangar.get_osw().to_owned()).collect()
+    }
+
+    /// Overtime worker hours and their cost over all hangars.
+    pub fn get_overtime(&self) -> (f64, f64){
+        let hours = self.hangar.iter().map(|hangar| hangar.get_total_overtime()).sum();
+        let cost = self.hangar.iter().map(|hangar| hangar.get_overtime_cost()).sum();
+        (hours, cost)
+    }
+
+    /// Mean utilization over all hangars.
+    pub fn get_mean_utilization(&self) -> f64{
+        if self.hangar.is_empty(){
+            return 0.0;
+        }
+        self.hangar.iter().map(|hangar| hangar.get_mean_utilization()).sum::<f64>() / self.hangar.len() as f64
+    }
+
+    /// Checks the input data of the added hangars (or all hangars if none were added).
+    pub fn validate(&self) -> ValidationReport{
+        let hangar_names: Vec<String> = self.hangar.iter().map(|hangar| hangar.get_name().to_owned()).collect();
+        validate::validate(&self.source, &hangar_names, &self.settings)
+    }
+
+    /// Loads stations, SOTs and tact plan of every hangar.
+    /// If no hangar was added, all hangars named in stations.csv are simulated.
+    pub fn load_data(&mut self) -> Result<(), PpeError>{  
+        if self.hangar.is_empty(){
+            let mut hangar_names = util::get_hangar_names(&self.source)?;
+            if hangar_names.is_empty(){
+                hangar_names.push(String::from(util::DEFAULT_HANGAR));
+            }
+            for name in hangar_names{
+                self.add_hangar(&name);
+            }
+        }
+        if self.hangar.len() > 1{
+            util::check_tactplan_hangars(&self.source)?;
         }
 
-        //Tact until the end, last MSN exits hangar
-        for _i in 0..hangar.get_number_of_stations(){
-            hangar.tact();
+        let sick_leave_settings = self.settings.get_sick_leave().clone();
+        let mut tact_plans = vec![];
+        for (i, hangar) in self.hangar.iter_mut().enumerate(){
+            // Read everything first, so a failing file leaves the hangar untouched
+            let name = hangar.get_name().to_owned();
+            let station_names = util::get_station_names(&self.source, &name)?;
+            if station_names.is_empty(){
+                return Err(PpeError::UnknownHangar { hangar: name });
+            }
+            let stations = util::get_stations(&self.source, &name)?;
+            let mut sots = util::load_sots(&self.source, &name, &self.settings.get_capacity_factors(&name), self.settings.get_dispatch(), self.settings.get_shift_system())?;
+            let sick_leave = util::load_sick_leave(&self.source, &name, sick_leave_settings.rate, &sots)?;
+            // Sampled absences replace the flat presence factor for BC absence of the SOTs they cover
+            if let Some(sick_leave) = &sick_leave{
+                for sot in sots.iter_mut().filter(|sot| sick_leave.has_rate(&sot.get_name())){
+                    let capacity = CapacityFactors { presence: 1.0, ..*sot.get_capacity() };
+                    sot.set_capacity(capacity);
+                }
+            }
+            let workers = util::load_workers(&self.source, &name, self.settings.get_shift_system())?;
+            util::assign_workers(&mut sots, workers, &name)?;
+            util::load_overtime(&self.source, &name, self.settings.get_overtime().cost_per_hour, &mut sots)?;
+            let calendar = match self.settings.get_calendar(){
+                Some(mut calendar) => {
+                    for exception in util::load_exceptions(&self.source, &name)?{
+                        calendar.add_exception(exception);
+                    }
+                    Some(calendar)
+                }
+                None => None,
+            };
+            util::load_staffing(&self.source, &name, calendar.as_ref(), self.settings.get_tacttime(), &mut sots)?;
+            let holidays = util::load_holidays(&self.source, &name, calendar.as_ref(), &sots)?;
+            let downtime = util::load_downtime(&self.source, &name, calendar.as_ref(), self.settings.get_tacttime(), &station_names)?;
+            let learning = util::load_learning(&self.source, &name, self.settings.get_learning(), &sots)?;
+            let msns = util::get_tactplan(&self.source, &name)?;
+            util::check_task_references(&self.source, &msns, &name, &station_names, &sots)?;
+
+            // Add Stations from CSV to Hangar
+            hangar.set_stations(stations);
+            hangar.set_sot(sots);
+            if let Some(calendar) = calendar{
+                hangar.set_calendar(calendar);
+            }
+            hangar.set_holidays(holidays);
+            hangar.set_lending(self.settings.get_lending().clone());
+            let unfinished = self.settings.get_unfinished_work().clone();
+            if unfinished.has_area() && unfinished.capacity <= 0.0{
+                return Err(PpeError::BadSettings { file: String::from("settings"), message: String::from("unfinished_work needs a capacity above 0 to work off OSW outside the line") });
+            }
+            hangar.set_unfinished_work(unfinished);
+            hangar.set_downtime(downtime);
+            hangar.set_hours_per_week(self.settings.get_shift_system().get_hours_per_week());
+            if let Some(learning) = learning{
+                hangar.set_learning(learning);
+            }
+            if let Some(sick_leave) = sick_leave{
+                // Replications use consecutive seeds, hangars are spaced apart to not share streams
+                hangar.set_sick_leave(sick_leave, sick_leave_settings.seed.wrapping_add(i as u64 * 1000));
+            }
+            tact_plans.push(msns);
         }
 
-        let osw = hangar.get_osw().to_owned();
-        util::write_to_output(osw);
-        let sot_util = hangar.get_sot_utilization();
-        util::write_sot_util(sot_util);
+        // Set MSNs to TactPlan
+        self.msns = tact_plans;
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> Result<(), PpeError>{
+        // Run Simulation, hangars are independent of each other
+        let tact_plans = std::mem::take(&mut self.msns);
+        for (hangar, msns) in self.hangar.iter_mut().zip(tact_plans){
+            // With predicted workloads, a copy of the hangar runs the tact plan as planned first
+            let predicted = match msns.iter().any(|msn| msn.has_predictions()){
+                true => {
+                    let mut planned = hangar.clone();
+                    let mut planned_msns = msns.clone();
+                    planned_msns.iter_mut().for_each(|msn| msn.use_predicted_workload());
+                    Engine::new(false).run(&mut TactSchedule::new(&mut planned, planned_msns, self.settings.get_tacttime(), self.settings.get_shift_system()));
+                    Some((msns.clone(), planned))
+                }
+                false => None,
+            };
+            // One MSN enters per tact, the line runs on until the last MSN exits the hangar
+            let mut engine = Engine::new(self.settings.get_log_events());
+            engine.run(&mut TactSchedule::new(hangar, msns, self.settings.get_tacttime(), self.settings.get_shift_system()));
+            if let Some(msn) = hangar.get_stopped_msn(){
+                let tacts = self.settings.get_unfinished_work().max_held_tacts;
+                return Err(PpeError::HoldLimit { hangar: hangar.get_name().to_string(), msn, tacts });
+            }
+            if let Some(events) = engine.get_log(){
+                util::write_events(events, hangar.get_name(), &mut self.sink)?;
+            }
+
+            util::write_to_output(hangar, &self.source, &mut self.sink)?;
+            util::write_deliveries(hangar.get_deliveries(), hangar.get_name(), &mut self.sink)?;
+            if let Some((msns, planned)) = predicted{
+                let rows = comparison::compare(&msns, planned.get_task_workload(), planned.get_task_osw(), hangar.get_task_workload(), hangar.get_task_osw());
+                util::write_comparison(&rows, hangar.get_name(), &mut self.sink)?;
+            }
+            let sot_util = hangar.get_sot_utilization();
+            util::write_sot_util(sot_util, hangar.get_name(), &mut self.sink)?;
+            util::write_worker_hours(hangar.get_worker_hours(), hangar.get_name(), &mut self.sink)?;
+            util::write_capacity(hangar.get_capacity_waterfall(), hangar.get_name(), &mut self.sink)?;
+            if !hangar.get_downtime_hours().is_empty(){
+                util::write_downtime(hangar.get_downtime_hours().to_owned(), hangar.get_name(), &mut self.sink)?;
+            }
+            if hangar.has_precedence(){
+                util::write_blocked(hangar.get_blocked_shares(), hangar.get_name(), &mut self.sink)?;
+            }
+            let overtime_hours = hangar.get_overtime_hours();
+            if !overtime_hours.is_empty(){
+                util::write_overtime(overtime_hours, hangar.get_name(), &mut self.sink)?;
+            }
+            if hangar.get_lending().is_enabled(){
+                util::write_lending(hangar.get_lent_hours(), hangar.get_name(), &mut self.sink)?;
+            }
+        }
+        util::write_summary(&self.hangar, &mut self.sink)?;
+        // Echo the resolved settings, so the run can be reproduced
+        let hangar_names = self.hangar.iter().map(|hangar| hangar.get_name().to_owned()).collect();
+        self.settings.set_hangars(hangar_names);
+        self.sink.write("settings.toml", self.settings.to_toml().into_bytes())?;
 
         Ok(())
     }
@@ -82,10 +257,39 @@ impl SIM{
 #[cfg(test)]
 mod tests{
     use super::*;
+    use crate::datasource::{fixture, InputFile};
+    use crate::lending::LendingPolicy;
+    use crate::dispatch::Dispatch;
+    use crate::unfinished::ExitPolicy;
+    use crate::shift::ShiftSystem;
+
+    /// Loads and runs the data with the settings changed by configure.
+    fn run_fixture(source: DataSource, configure: impl FnOnce(&mut Settings)) -> SIM{
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        configure(&mut sim.settings);
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+        sim
+    }
+
+    /// One MSN with one MECH task at a single station, MECH has the given headcount.
+    fn one_task(bc: u32, workload: f64) -> DataSource{
+        DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\n")
+            .with_data(InputFile::Sot, &format!("sot,bc,cvat,stations\nMECH,{},1.0,S1\n", bc))
+            .with_data(InputFile::Tasks, &format!("station,sot,version,workload\nS1,MECH,A,{}\n", workload))
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n")
+    }
+
+    /// A tact of 10 hours without flat BC inefficiency, so a worker has 10 productive hours per tact.
+    fn ten_hour_tact(settings: &mut Settings){
+        settings.set_tacttime(10.0);
+        settings.set_bc_inefficiency(0.0);
+    }
 
     #[test]
     fn hangar_tacts_until_end(){
-        let mut sim = SIM::new();
+        let mut sim = SIM::with_io(fixture(), OutputSink::memory());
         sim.add_hangar("1");
         sim.load_data().unwrap();
         sim.run().unwrap();
@@ -96,4 +300,540 @@ mod tests{
         println!("{:#?}", sim.get_hangar(0).get_all_stations());
         assert!(sim.get_hangar(0).get_last_station().get_current_msn().is_none())
     }
+
+    #[test]
+    fn outputs_written_to_sink(){
+        let mut sim = SIM::with_io(fixture(), OutputSink::memory());
+        sim.add_hangar("1");
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        let output = sim.get_output_sink().get("output_1.csv").unwrap();
+        assert!(output.starts_with("msn,version,osw"));
+        assert_eq!(output.lines().count(), 4);
+        assert!(sim.get_output_sink().get("sot_util_1.csv").is_some());
+        assert!(sim.get_output_sink().get("worker_hours_1.csv").unwrap().starts_with("tact,MECH-1,MECH-2,MECH-3,MECH-4,ELEC-1,ELEC-2"));
+        assert!(sim.get_output_sink().get("summary.csv").is_some());
+        assert!(sim.get_output_sink().get("settings.toml").unwrap().contains("production_rate = 7.5"));
+    }
+
+    #[test]
+    fn output_has_dates_with_calendar(){
+        let source = fixture().with_data(InputFile::Exceptions, "date,kind,reason\n2026-01-06,holiday,test\n");
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        sim.settings.set_start_date(chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        let output = sim.get_output_sink().get("output_1.csv").unwrap();
+        assert!(output.lines().next().unwrap().ends_with("osw,entry_date,exit_date,learning_factor"));
+        assert!(output.lines().nth(1).unwrap().contains(",2026-01-05,"));
+        assert!(sim.get_output_sink().get("settings.toml").unwrap().contains("start_date = \"2026-01-05\""));
+    }
+
+    #[test]
+    fn sick_leave_depends_on_seed(){
+        let total_osw = |seed| {
+            let sim = run_fixture(fixture(), |settings| {
+                settings.set_sick_leave_rate(0.3);
+                settings.set_seed(seed);
+            });
+            sim.get_osw().iter().sum::<f64>()
+        };
+        assert_eq!(total_osw(3), total_osw(3));
+        assert!((1..10).any(|seed| total_osw(seed) != total_osw(0)));
+
+        // Everybody is always off sick, nothing of the 15 hours gets done
+        let sick = run_fixture(one_task(2, 15.0), |settings| {
+            ten_hour_tact(settings);
+            settings.set_sick_leave_rate(1.0);
+        });
+        assert_eq!(sick.get_osw(), vec![15.0]);
+    }
+
+    #[test]
+    fn sick_leave_of_one_sot_keeps_presence_of_others(){
+        let source = fixture().with_data(InputFile::SickLeave, "sot,rate\nMECH,0.0\n");
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        let waterfall = sim.get_hangar(0).get_capacity_waterfall();
+        // MECH samples at rate 0, ELEC has no rate and keeps the flat BC inefficiency
+        assert_eq!(waterfall["MECH"].presence, 0.0);
+        assert!((waterfall["ELEC"].presence - 0.086 * waterfall["ELEC"].scheduled).abs() < 1e-6);
+    }
+
+    #[test]
+    fn holidays_raise_osw(){
+        let start = chrono::NaiveDate::from_ymd_opt(2026, 8, 3).unwrap();
+        let total_osw = |holidays: &str| {
+            let sim = run_fixture(fixture().with_data(InputFile::Holidays, holidays), |settings| settings.set_start_date(start));
+            sim.get_osw().iter().sum::<f64>()
+        };
+        let without = total_osw("sot,start_date,end_date,headcount\n");
+        let with = total_osw("sot,start_date,end_date,headcount\nMECH,2026-08-03,2026-08-14,2\n");
+        let whole_sot = total_osw("worker,sot,start_date,end_date\n,MECH,2026-08-03,2026-08-14\n");
+        assert!(without < with && with < whole_sot, "{} {} {}", without, with, whole_sot);
+
+        // One of two MECHs is on holiday, the other works 10 of the 15 hours
+        let source = one_task(2, 15.0).with_data(InputFile::Holidays, "sot,start_date,end_date,headcount\nMECH,2026-08-03,2026-08-14,1\n");
+        let holiday = run_fixture(source, |settings| {
+            ten_hour_tact(settings);
+            settings.set_start_date(start);
+        });
+        assert_eq!(holiday.get_osw(), vec![5.0]);
+
+        // Without a start date the holidays can't be placed
+        let source = fixture().with_data(InputFile::Holidays, "sot,start_date\nMECH,2026-08-03\n");
+        let mut undated = SIM::with_io(source, OutputSink::memory());
+        match undated.load_data(){
+            Err(PpeError::BadRow { line, column, .. }) => assert_eq!((line, column.as_deref()), (Some(2), Some("start_date"))),
+            other => panic!("expected a bad row, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn learning_curve_raises_workload_of_first_units(){
+        let source = fixture().with_data(InputFile::Learning, "sot,version,rate,floor\n,,0.8,0.5\n");
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        let factors = sim.get_hangar(0).get_learning_factors();
+        assert_eq!(factors[0], 2.0);
+        assert!(factors.windows(2).all(|pair| pair[1] <= pair[0]));
+        let output = sim.get_output_sink().get("output_1.csv").unwrap();
+        assert!(output.lines().nth(1).unwrap().ends_with(",2"));
+    }
+
+    #[test]
+    fn overtime_lowers_osw_at_a_cost(){
+        let run = |overtime: Option<&str>| {
+            let mut source = fixture();
+            if let Some(overtime) = overtime{
+                source = source.with_data(InputFile::Overtime, overtime);
+            }
+            run_fixture(source, |settings| {
+                settings.set_tacttime(1.0);
+                settings.set_overtime_cost(50.0);
+            })
+        };
+        let without = run(None);
+        let with = run(Some("sot,max_per_tact,max_per_week\nMECH,2,4\nELEC,2,\n"));
+        let (hours, cost) = with.get_overtime();
+        assert!(hours > 0.0 && cost == hours * 50.0);
+        assert!(with.get_osw().iter().sum::<f64>() < without.get_osw().iter().sum::<f64>());
+        assert!(with.get_output_sink().get("overtime_1.csv").unwrap().starts_with("tact,MECH,ELEC"));
+        assert!(without.get_output_sink().get("overtime_1.csv").is_none());
+
+        // 10 hours in the tact and 2 of the 3 overtime hours allowed to finish the 12 hours
+        let source = one_task(1, 12.0).with_data(InputFile::Overtime, "sot,max_per_tact\nMECH,3\n");
+        let exact = run_fixture(source, |settings| {
+            ten_hour_tact(settings);
+            settings.set_overtime_cost(50.0);
+        });
+        assert_eq!(exact.get_osw(), vec![0.0]);
+        assert_eq!(exact.get_overtime(), (2.0, 100.0));
+    }
+
+    #[test]
+    fn staffing_ramp_up_lowers_osw(){
+        let total_osw = |staffing: &str| {
+            let sim = run_fixture(fixture().with_data(InputFile::Staffing, staffing), |settings| settings.set_tacttime(5.0));
+            (sim.get_osw().iter().sum::<f64>(), sim.get_output_sink().get("worker_hours_1.csv").unwrap().to_owned())
+        };
+        let (without, _) = total_osw("sot,from_tact,bc\n");
+        let (with, worker_hours) = total_osw("sot,from_tact,bc,cvat\nELEC,1,4,\nMECH,2,2,0.9\n");
+        assert!(with < without, "{} {}", with, without);
+        assert!(worker_hours.starts_with("tact,MECH-1,MECH-2,MECH-3,MECH-4,ELEC-1,ELEC-2,ELEC-3,ELEC-4"));
+
+        // A second MECH from the start, both at a cvat of 0.5, does 10 of the 25 hours
+        let source = one_task(1, 25.0).with_data(InputFile::Staffing, "sot,from_tact,bc,cvat\nMECH,0,2,0.5\n");
+        let ramped = run_fixture(source, ten_hour_tact);
+        assert_eq!(ramped.get_osw(), vec![15.0]);
+    }
+
+    #[test]
+    fn downtime_and_efficiency_raise_osw(){
+        let run = |downtime: &str, efficiency: f64| {
+            run_fixture(fixture().with_data(InputFile::Downtime, downtime), |settings| {
+                settings.set_tacttime(5.0);
+                settings.set_technical_efficiency(efficiency);
+            })
+        };
+        let plain = run("start_tact,duration\n", 1.0);
+        let total_osw = |sim: &SIM| sim.get_osw().iter().sum::<f64>();
+        assert!(plain.get_output_sink().get("downtime_1.csv").is_none());
+
+        let down = run("station,start_tact,duration,reason\nS1,1,5,crane\n,2,2.5,power\n", 1.0);
+        assert!(total_osw(&down) > total_osw(&plain));
+        let downtime = down.get_output_sink().get("downtime_1.csv").unwrap();
+        assert!(downtime.starts_with("tact,crane,power\n0,0,0\n1,5,0\n2,0,2.5\n"), "{}", downtime);
+
+        let slow = run("start_tact,duration\n", 0.9);
+        assert!(total_osw(&slow) > total_osw(&plain));
+
+        // The station is down for 4 of the 10 hours and the workers work at 90%: 5.4 of the 15 hours are done
+        let source = one_task(1, 15.0).with_data(InputFile::Downtime, "station,start_tact,duration\nS1,0,4\n");
+        let exact = run_fixture(source, |settings| {
+            ten_hour_tact(settings);
+            settings.set_technical_efficiency(0.9);
+        });
+        assert!((exact.get_osw()[0] - 9.6).abs() < 1e-9, "{:?}", exact.get_osw());
+    }
+
+    #[test]
+    fn successors_wait_and_report_blocked_capacity(){
+        let run = |tasks: &str| {
+            let source = fixture()
+                .with_data(InputFile::Tasks, tasks)
+                .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+            let mut sim = SIM::with_io(source, OutputSink::memory());
+            sim.settings.set_tacttime(5.0);
+            sim.load_data().unwrap();
+            sim.run().unwrap();
+            sim
+        };
+        let tact_rows = |table: &str| -> Vec<Vec<f64>> {
+            table.lines().skip(1).map(|line| line.split(',').skip(1).map(|value| value.parse().unwrap()).collect()).collect()
+        };
+        let free = run("station,sot,version,workload\nS1,MECH,A,40\nS1,ELEC,A,5\n");
+        assert!(free.get_output_sink().get("blocked_1.csv").is_none());
+        assert!(tact_rows(free.get_output_sink().get("sot_util_1.csv").unwrap())[0][1] > 0.0);
+
+        // ELEC waits at S1 for T1, which MECH finishes in the second tact, and burns its task as open work at S3
+        let chained = run("station,sot,version,workload,id,predecessors\nS1,MECH,A,20,T1,\nS1,ELEC,A,5,,T1\n");
+        let util = tact_rows(chained.get_output_sink().get("sot_util_1.csv").unwrap());
+        let blocked = tact_rows(chained.get_output_sink().get("blocked_1.csv").unwrap());
+        assert!(chained.get_output_sink().get("blocked_1.csv").unwrap().starts_with("tact,MECH,ELEC\n"));
+        assert_eq!(util[0][1], 0.0);
+        assert!(blocked[0][1] > 0.0 && blocked[0][1] < 1.0, "{:?}", blocked);
+        assert_eq!(blocked[0][0], 0.0);
+        assert!(util[2][1] > 0.0);
+        assert_eq!(blocked[2][1], 0.0);
+
+        // A successor freed at the same station is worked on in the same tact
+        let same_sot = run("station,sot,version,workload,id,predecessors\nS1,MECH,A,5,T1,\nS1,MECH,A,5,,T1\n");
+        let util = tact_rows(same_sot.get_output_sink().get("sot_util_1.csv").unwrap());
+        assert!(util[0][0] > 0.5 && util[1][0] == 0.0, "{:?}", util);
+        assert_eq!(chained.get_osw(), vec![0.0]);
+    }
+
+    #[test]
+    fn capacity_waterfall_per_sot(){
+        let mut sim = SIM::with_io(fixture(), OutputSink::memory());
+        sim.settings.set_tacttime(5.0);
+        sim.settings.set_shift_efficiency(0.9);
+        sim.settings.set_learning_rate(0.8);
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        let capacity = sim.get_output_sink().get("capacity_1.csv").unwrap();
+        let rows: Vec<Vec<f64>> = capacity.lines().skip(1)
+            .map(|line| line.split(',').skip(1).map(|value| value.parse().unwrap()).collect())
+            .collect();
+        assert!(capacity.starts_with("sot,scheduled_hours,presence,cvat,hangar_efficiency,shift_efficiency,learning,productive_hours\nMECH,"));
+        assert_eq!(rows.len(), 3);
+        // MECH: 4 workers over 7 tacts of 5 hours, 0.914 present at cvat 0.8 and shift efficiency 0.9,
+        // part of which goes into the learning of the first units
+        assert_eq!(rows[0][0], 140.0);
+        assert!((rows[0][5] + rows[0][6] - 140.0 * 0.914 * 0.8 * 0.9).abs() < 1e-9);
+        assert!(rows[0][5] > 0.0);
+        assert_eq!(rows[2][0], rows[0][0] + rows[1][0]);
+    }
+
+    #[test]
+    fn events_are_logged_on_request(){
+        let run = |log_events| {
+            let mut sim = SIM::with_io(fixture(), OutputSink::memory());
+            sim.settings.set_tacttime(7.0);
+            sim.settings.set_log_events(log_events);
+            sim.load_data().unwrap();
+            sim.run().unwrap();
+            sim
+        };
+        assert!(run(false).get_output_sink().get("events_1.csv").is_none());
+        let logged = run(true);
+        let events = logged.get_output_sink().get("events_1.csv").unwrap();
+        assert!(events.starts_with("time,event,detail\n0,shift_start,early\n0,msn_entry,1\n0,tact_start,0\n"), "{}", events);
+        assert!(events.contains("\n42,msn_exit,3\n"));
+    }
+
+    #[test]
+    fn lending_lowers_osw(){
+        let run = |policy| {
+            let source = DataSource::memory()
+                .with_data(InputFile::Stations, "station\nS1\nS2\n")
+                .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1\nELEC,2,1.0,S2\n")
+                .with_data(InputFile::Tasks, "station,sot,version,workload\nS1,MECH,A,30\nS2,ELEC,A,5\n")
+                .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+            let mut sim = SIM::with_io(source, OutputSink::memory());
+            sim.settings.set_tacttime(10.0);
+            sim.settings.set_bc_inefficiency(0.0);
+            sim.settings.set_lending_policy(policy);
+            sim.load_data().unwrap();
+            sim.run().unwrap();
+            sim
+        };
+        let off = run(LendingPolicy::Off);
+        let adjacent = run(LendingPolicy::Adjacent);
+        assert!(off.get_output_sink().get("lending_1.csv").is_none());
+        assert!(adjacent.get_osw()[0] < off.get_osw()[0]);
+        let lending = adjacent.get_output_sink().get("lending_1.csv").unwrap();
+        assert!(lending.starts_with("tact,MECH lent,MECH borrowed,ELEC lent,ELEC borrowed"));
+    }
+
+    #[test]
+    fn critical_path_dispatch_lowers_osw(){
+        let run = |sot: &str, dispatch: Dispatch| {
+            let source = DataSource::memory()
+                .with_data(InputFile::Stations, "station\nS1\n")
+                .with_data(InputFile::Sot, sot)
+                .with_data(InputFile::Tasks, "station,sot,version,workload,id,predecessors\nS1,MECH,A,10,T3,\nS1,MECH,A,10,T1,\nS1,ELEC,A,10,T2,T1\n")
+                .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+            let mut sim = SIM::with_io(source, OutputSink::memory());
+            sim.settings.set_tacttime(10.0);
+            // One shift per tact, so the tact is worked in one slice
+            sim.settings.set_shift_system(ShiftSystem::uniform(1, 10.0, 0.0, 5.0));
+            sim.settings.set_bc_inefficiency(0.0);
+            sim.settings.set_dispatch(dispatch);
+            sim.load_data().unwrap();
+            sim.run().unwrap();
+            sim.get_osw()[0]
+        };
+        let sot = "sot,bc,cvat,stations\nMECH,1,1.0,S1\nELEC,1,1.0,S1\n";
+        // Downstream works on T3 first and leaves ELEC waiting for T1
+        assert!((run(sot, Dispatch::Downstream) - 20.0).abs() < 1e-9);
+        assert!((run(sot, Dispatch::CriticalPath) - 10.0).abs() < 1e-9);
+        let per_sot = "sot,bc,cvat,stations,dispatch\nMECH,1,1.0,S1,critical-path\nELEC,1,1.0,S1,\n";
+        assert!((run(per_sot, Dispatch::Downstream) - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unfinished_work_policies_delay_delivery(){
+        let run = |policy: ExitPolicy| {
+            let source = DataSource::memory()
+                .with_data(InputFile::Stations, "station\nS1\n")
+                .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1\n")
+                .with_data(InputFile::Tasks, "station,sot,version,workload\nS1,MECH,A,25\n")
+                .with_data(InputFile::TactPlan, "msn,version\n1,A\n2,A\n");
+            let mut sim = SIM::with_io(source, OutputSink::memory());
+            sim.settings.set_tacttime(10.0);
+            sim.settings.set_bc_inefficiency(0.0);
+            sim.settings.set_exit_policy(policy);
+            sim.settings.set_exit_capacity(10.0);
+            sim.load_data().unwrap();
+            sim.run().unwrap();
+            sim
+        };
+        let delays = |sim: &SIM| -> Vec<(f64, f64)> {
+            sim.get_hangar(0).get_deliveries().iter().map(|delivery| (delivery.exit, delivery.get_delay())).collect()
+        };
+        let sink = run(ExitPolicy::Sink);
+        assert_eq!(delays(&sink), vec![(10.0, 0.0), (20.0, 0.0)]);
+        assert!(sink.get_output_sink().get("delivery_1.csv").unwrap().starts_with("msn,osw,planned_exit,exit,delivery,delay\n1,15,10,10,10,0"));
+        // The rework area works off 15 hours of each MSN at 1 hour per line hour, one after the other
+        assert_eq!(delays(&run(ExitPolicy::Rework)), vec![(10.0, 15.0), (20.0, 20.0)]);
+        // Held until done, MSN 2 only enters once MSN 1 left
+        let hold = run(ExitPolicy::Hold);
+        assert_eq!(delays(&hold), vec![(30.0, 20.0), (60.0, 40.0)]);
+        assert_eq!(hold.get_osw(), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn hold_waits_through_a_tact_without_capacity(){
+        let run = |tasks: &str| {
+            let source = DataSource::memory()
+                .with_data(InputFile::Stations, "station\nS1\n")
+                .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1\n")
+                .with_data(InputFile::Tasks, tasks)
+                .with_data(InputFile::TactPlan, "msn,version\n1,A\n2,A\n")
+                .with_data(InputFile::Downtime, "station,start_tact,duration\nS1,1,10\n");
+            let mut sim = SIM::with_io(source, OutputSink::memory());
+            sim.settings.set_tacttime(10.0);
+            sim.settings.set_bc_inefficiency(0.0);
+            sim.settings.set_exit_policy(ExitPolicy::Hold);
+            sim.load_data().unwrap();
+            sim.run().unwrap();
+            sim
+        };
+        let exits = |sim: &SIM| -> Vec<f64> { sim.get_hangar(0).get_deliveries().iter().map(|delivery| delivery.exit).collect() };
+        // The station is down in tact 1, MSN 1 stays and is done in tact 3
+        let down = run("station,sot,version,workload\nS1,MECH,A,25\n");
+        assert_eq!(exits(&down), vec![40.0, 70.0]);
+        assert_eq!(down.get_osw(), vec![0.0, 0.0]);
+        // Nobody holds the qualification, the MSN leaves with the task open
+        let unqualified = run("station,sot,version,workload,qualifications\nS1,MECH,A,25,\nS1,MECH,A,5,welding\n");
+        assert_eq!(exits(&unqualified), vec![40.0, 70.0]);
+        assert_eq!(unqualified.get_osw(), vec![5.0, 5.0]);
+    }
+
+    #[test]
+    fn hold_stops_after_the_held_tact_limit(){
+        let run = |downtime: &str| {
+            let source = DataSource::memory()
+                .with_data(InputFile::Stations, "station\nS1\nS2\n")
+                .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1;S2\n")
+                .with_data(InputFile::Tasks, "station,sot,version,workload\nS2,MECH,A,25\n")
+                .with_data(InputFile::TactPlan, "msn,version\n1,A\n")
+                .with_data(InputFile::Downtime, downtime);
+            let mut sim = SIM::with_io(source, OutputSink::memory());
+            sim.settings.set_tacttime(10.0);
+            sim.settings.set_exit_policy(ExitPolicy::Hold);
+            sim.settings.set_max_held_tacts(5);
+            sim.load_data().and_then(|_| sim.run())
+        };
+        // The crane is down practically for good, the MSN is held for 5 tacts before the line stops
+        match run("station,start_tact,duration,reason\nS2,0,1e9,crane\n"){
+            Err(PpeError::HoldLimit { hangar, msn, tacts }) => assert_eq!((hangar.as_str(), msn, tacts), ("1", 1, 5)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match run("station,start_tact,duration,reason\nS2,0,inf,crane\n"){
+            Err(PpeError::BadRow { line, column, .. }) => assert_eq!((line, column.as_deref()), (Some(2), Some("duration"))),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn exit_area_needs_capacity(){
+        let mut sim = SIM::with_io(fixture(), OutputSink::memory());
+        sim.settings.set_exit_policy(ExitPolicy::DeliveryCentre);
+        assert!(matches!(sim.load_data(), Err(PpeError::BadSettings { .. })));
+    }
+
+    #[test]
+    fn predicted_workload_is_compared_with_actual(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload,predicted_workload\nS1,MECH,A,15,8\nS1,MECH,A,2,\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        sim.settings.set_tacttime(10.0);
+        sim.settings.set_bc_inefficiency(0.0);
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        // The actual workload is what the line works on
+        assert!((sim.get_osw()[0] - 7.0).abs() < 1e-9);
+        let comparison = sim.get_output_sink().get("comparison_1.csv").unwrap();
+        assert_eq!(comparison, "msn,sot,station,predicted_workload,actual_workload,workload_error,predicted_osw,actual_osw\n1,MECH,S1,10,17,-7,0,7\n");
+        let mut without = SIM::with_io(fixture(), OutputSink::memory());
+        without.load_data().unwrap();
+        without.run().unwrap();
+        assert!(without.get_output_sink().get("comparison_1.csv").is_none());
+    }
+
+    #[test]
+    fn comparison_uses_workload_after_learning(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,1,1.0,S1\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload,predicted_workload\nS1,MECH,A,15,8\nS1,MECH,A,2,\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n")
+            .with_data(InputFile::Learning, "sot,version,rate,floor\n,,0.8,0.5\n");
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        sim.settings.set_tacttime(10.0);
+        sim.settings.set_bc_inefficiency(0.0);
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        // The first unit needs twice the workload in both runs, the OSW is what is left of it
+        assert!((sim.get_osw()[0] - 24.0).abs() < 1e-9);
+        let comparison = sim.get_output_sink().get("comparison_1.csv").unwrap();
+        assert_eq!(comparison.lines().nth(1), Some("1,MECH,S1,20,34,-14,10,24"));
+    }
+
+    #[test]
+    fn scenarios_run_on_separate_threads(){
+        let runs: Vec<_> = [7.5, 9.0].into_iter().map(|tact_time| {
+            let mut sim = SIM::with_io(fixture(), OutputSink::memory());
+            sim.set_tacttime(tact_time);
+            std::thread::spawn(move || {
+                sim.load_data().unwrap();
+                sim.run().unwrap();
+                sim.get_osw()
+            })
+        }).collect();
+        for run in runs{
+            assert_eq!(run.join().unwrap().len(), 3);
+        }
+    }
+
+    // Performance test, run with `cargo test --release -- --ignored full_year`. A release build took
+    // 0.7 to 0.8 seconds, the budget of 2 seconds leaves room for a busy machine.
+    #[test]
+    #[ignore = "performance test for release builds"]
+    fn full_year_plan_runs_within_budget(){
+        // 12 stations, 10 SOTs, 20 versions of 1500 tasks each with chains of predecessors, 250 MSNs
+        let stations: Vec<String> = (1..=12).map(|i| format!("S{}", i)).collect();
+        let mut sot = String::from("sot,bc,cvat,stations,dispatch\n");
+        for i in 0..10{
+            sot += &format!("SOT{},6,0.8,{};{},critical-path\n", i, stations[i], stations[i + 1]);
+        }
+        let mut tasks = String::from("station,sot,version,workload,id,predecessors\n");
+        for version in 0..20{
+            for k in 0..1500{
+                let predecessor = match k % 10{
+                    0 => String::new(),
+                    _ => format!("T{}", k - 1),
+                };
+                tasks += &format!("{},SOT{},V{},{},T{},{}\n", stations[k % 11], k % 10, version, 0.5 + (k % 7) as f64 * 0.3, k, predecessor);
+            }
+        }
+        let tact_plan: String = (1..=250).map(|msn| format!("{},V{}\n", msn, msn % 20)).collect();
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, &format!("station\n{}\n", stations.join("\n")))
+            .with_data(InputFile::Sot, &sot)
+            .with_data(InputFile::Tasks, &tasks)
+            .with_data(InputFile::TactPlan, &format!("msn,version\n{}", tact_plan));
+
+        let start = std::time::Instant::now();
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(sim.get_osw().len(), 250);
+        assert!(elapsed.as_secs_f64() < 2.0, "{:?}", elapsed);
+    }
+
+    #[test]
+    fn hangars_are_simulated_separately(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station,hangar\nS1,8\nS2,8\nS1,9\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations,hangar\nMECH,4,0.8,S1;S2,8\nMECH,2,0.8,S1,9\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload,hangar\nS1,MECH,A,40,8\nS2,MECH,A,30,8\nS1,MECH,A,10,9\n")
+            .with_data(InputFile::TactPlan, "msn,version,hangar\n1,A,8\n2,A,9\n3,A,8\n");
+        let mut sim = SIM::with_io(source, OutputSink::memory());
+        sim.load_data().unwrap();
+        sim.run().unwrap();
+
+        assert_eq!(sim.get_hangars().len(), 2);
+        assert_eq!(sim.get_hangar(0).get_osw().len(), 2);
+        assert_eq!(sim.get_hangar(1).get_osw().len(), 1);
+        let summary = sim.get_output_sink().get("summary.csv").unwrap();
+        assert_eq!(summary.lines().count(), 4);
+        assert!(summary.lines().last().unwrap().starts_with("total,3,"));
+    }
+
+    #[test]
+    fn msn_without_hangar_is_rejected_with_several_hangars(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station,hangar\nS1,8\nS1,9\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,2,0.8,S1\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload\nS1,MECH,A,10\n")
+            .with_data(InputFile::TactPlan, "msn,version,hangar\n1,A,8\n2,A,\n");
+        let mut sim = SIM::with_io(source.clone(), OutputSink::memory());
+        match sim.load_data(){
+            Err(PpeError::BadRow { line, column, .. }) => assert_eq!((line, column.as_deref()), (Some(3), Some("hangar"))),
+            other => panic!("expected a bad row, got {:?}", other.err()),
+        }
+        // With one hangar simulated the row belongs to it
+        let mut single = SIM::with_io(source, OutputSink::memory());
+        single.add_hangar("9");
+        single.load_data().unwrap();
+        single.run().unwrap();
+        assert_eq!(single.get_hangar(0).get_osw().len(), 1);
+    }
 }
\ No newline at end of file
@@ -1,25 +1,362 @@
 use crate::task::Task;
+use crate::worker::Worker;
+use crate::absence::SickLeave;
+use crate::holiday::HolidayPlan;
+use crate::overtime::OvertimePolicy;
+use crate::staffing::StaffingChange;
+use crate::capacity::{CapacityFactors, CapacityWaterfall};
+use crate::dispatch::{DispatchRule, Downstream};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::sync::Arc;
+use rand::Rng;
 #[derive(Debug)]
 #[derive(Clone)]
 pub struct SOT{
     name: String,
     station_assignment: Vec<String>,
-    workers: u16,
-    cvat: f64,
+    workers: Vec<Worker>,
+    // Workers come from workers.csv instead of the bc headcount
+    listed_workers: bool,
+    capacity: CapacityFactors,
+    // Hours each capacity factor took away so far
+    waterfall: CapacityWaterfall,
     utilization: Vec<f64>,
+    // Shifts the SOT works, all shifts of the line if empty
+    shifts: Vec<String>,
+    // Part of the tact time the SOT is present, from its shifts
+    shift_share: f64,
+    // Hours each worker is present in the current slice of the tact, same order as workers
+    present_hours: Vec<f64>,
+    // Productive hours per hour of available time of each worker in the current slice
+    worker_rates: Vec<f64>,
+    // Available time each worker has left in the current slice
+    worker_time: Vec<f64>,
+    available_time: f64,
+    // Time each worker may still spend on a station that is partly down in the current slice
+    station_budgets: HashMap<usize, Vec<f64>>,
+    // Hours each worker was present and worked in the slices of the current tact so far
+    tact_present: Vec<f64>,
+    tact_worked: Vec<f64>,
+    // Productive hours used and available and the available time in the current tact so far
+    tact_output: f64,
+    tact_capacity: f64,
+    tact_available: f64,
+    // Hours lent to and borrowed from other SOTs per tact, the last entry is the current tact
+    lent: Vec<f64>,
+    borrowed: Vec<f64>,
+    // Share of the available time per tact the SOT had nothing to do but tasks waiting for predecessors
+    blocked: Vec<f64>,
+    // No overtime without a policy
+    overtime: Option<OvertimePolicy>,
+    // Overtime worker hours per tact and overtime hours per worker in the current week
+    overtime_hours: Vec<f64>,
+    week_overtime: f64,
+    // Headcount changes by tact, in order
+    staffing: Vec<StaffingChange>,
+    // Anonymous workers on holiday in the current tact, averaged over its days
+    holiday_headcount: f64,
+    // Order in which the SOT works on the tasks it can reach
+    dispatch: Arc<dyn DispatchRule>,
 }
 
 impl SOT{
+    /// Creates a SOT with the given headcount of anonymous workers named <SOT>-1, <SOT>-2, ...
     pub fn new(name: &str, station_assignment: Vec<String>, workers: u16, cvat: f64) -> SOT{
         SOT{
             station_assignment,
-            workers,
-            cvat,
+            workers: (1..=workers).map(|i| Worker::new(&format!("{}-{}", name, i), name, 1.0)).collect(),
+            listed_workers: false,
+            capacity: CapacityFactors::new(cvat),
+            waterfall: CapacityWaterfall::default(),
             name : String::from(name),
-            utilization: vec![]
+            utilization: vec![],
+            shifts: vec![],
+            shift_share: 1.0,
+            present_hours: vec![],
+            worker_rates: vec![],
+            worker_time: vec![],
+            available_time: 0.0,
+            station_budgets: HashMap::new(),
+            tact_present: vec![],
+            tact_worked: vec![],
+            tact_output: 0.0,
+            tact_capacity: 0.0,
+            tact_available: 0.0,
+            lent: vec![],
+            borrowed: vec![],
+            blocked: vec![],
+            overtime: None,
+            overtime_hours: vec![],
+            week_overtime: 0.0,
+            staffing: vec![],
+            holiday_headcount: 0.0,
+            dispatch: Arc::new(Downstream),
         }
     }
 
+    /// Replaces the anonymous workers by named ones from workers.csv.
+    pub fn set_workers(&mut self, workers: Vec<Worker>){
+        self.workers = workers;
+        self.listed_workers = true;
+    }
+
+    pub fn has_listed_workers(&self) -> bool{
+        self.listed_workers
+    }
+
+    /// Whether the worker is one of the SOT's workers, or an anonymous worker the staffing plan hires.
+    pub fn knows_worker(&self, id: &str) -> bool{
+        let max_headcount = self.staffing.iter().map(|change| change.bc as usize).max().unwrap_or(0);
+        self.workers.iter().any(|worker| worker.get_id() == id)
+            || (1..=max_headcount).any(|i| format!("{}-{}", self.name, i) == id)
+    }
+
+    pub fn get_workers(&self) -> &Vec<Worker>{
+        &self.workers
+    }
+
+    pub fn set_shifts(&mut self, shifts: Vec<String>, shift_share: f64){
+        self.shifts = shifts;
+        self.shift_share = shift_share;
+    }
+
+    pub fn get_shifts(&self) -> &Vec<String>{
+        &self.shifts
+    }
+
+    pub fn set_capacity(&mut self, capacity: CapacityFactors){
+        self.capacity = capacity;
+    }
+
+    pub fn get_capacity(&self) -> &CapacityFactors{
+        &self.capacity
+    }
+
+    pub fn get_waterfall(&self) -> &CapacityWaterfall{
+        &self.waterfall
+    }
+
+    pub fn set_dispatch_rule(&mut self, dispatch: Arc<dyn DispatchRule>){
+        self.dispatch = dispatch;
+    }
+
+    pub fn get_dispatch_rule(&self) -> &dyn DispatchRule{
+        self.dispatch.as_ref()
+    }
+
+    /// Hours the SOT works in a tact of the given length
+    pub fn get_available_time(&self, tacttime: f64) -> f64{
+        tacttime * self.shift_share
+    }
+
+    pub fn set_staffing(&mut self, mut staffing: Vec<StaffingChange>){
+        staffing.sort_by_key(|change| change.tact);
+        self.staffing = staffing;
+    }
+
+    /// Applies the staffing changes from the given tact on. Anonymous workers are hired as
+    /// <SOT>-n after the existing ones and the last hired leave first.
+    pub fn apply_staffing(&mut self, tact: usize){
+        let changes: Vec<StaffingChange> = self.staffing.iter().filter(|change| change.tact == tact).cloned().collect();
+        for change in changes{
+            let tacts_so_far = self.utilization.len();
+            for i in self.workers.len()..change.bc as usize{
+                let mut worker = Worker::new(&format!("{}-{}", self.name, i + 1), &self.name, 1.0);
+                (0..tacts_so_far).for_each(|_| worker.add_hours(0.0));
+                self.workers.push(worker);
+            }
+            for (i, worker) in self.workers.iter_mut().enumerate(){
+                worker.set_employed(i < change.bc as usize);
+            }
+            if let Some(cvat) = change.cvat{
+                self.capacity.cvat = cvat;
+            }
+        }
+    }
+
+    /// Whether the SOT can work on the task in the given tact or a later one: it has capacity and an
+    /// employed worker holding the task's qualifications, or the staffing plan brings them later.
+    /// Absences and holidays only keep workers away for a while.
+    pub fn can_work_on(&self, task: &Task, tact: usize) -> bool{
+        let later: Vec<&StaffingChange> = self.staffing.iter().filter(|change| change.tact > tact).collect();
+        let has_capacity = self.capacity.get_rate() > 0.0 || later.iter().any(|change| change.cvat.is_some_and(|cvat| cvat > 0.0));
+        if !has_capacity || self.shift_share <= 0.0{
+            return false;
+        }
+        // Staffing employs the first bc workers and hires anonymous ones without qualifications
+        let later_bc = later.iter().map(|change| change.bc as usize).max().unwrap_or(0);
+        let hired = later_bc > self.workers.len() && task.get_qualifications().is_empty();
+        hired || self.workers.iter().enumerate().any(|(i, worker)| {
+            (worker.is_employed() || i < later_bc) && worker.get_efficiency() > 0.0 && worker.is_qualified(task.get_qualifications())
+        })
+    }
+
+    /// Draws which workers are sick in the coming tact and returns their ids. Every worker is back
+    /// at the start of the tact, the sick ones stay present until set_absent is called for them.
+    pub fn sample_absence(&mut self, sick_leave: &SickLeave, month: Option<u32>, rng: &mut impl Rng) -> Vec<String>{
+        let mut sick = vec![];
+        for worker in self.workers.iter_mut().filter(|worker| worker.is_employed()){
+            worker.set_absent(false);
+            if sick_leave.sample(&self.name, month, rng){
+                sick.push(worker.get_id().to_owned());
+            }
+        }
+        sick
+    }
+
+    /// Marks a worker as off from now on. Returns false if the worker is not one of the SOT's.
+    pub fn set_absent(&mut self, id: &str) -> bool{
+        match self.workers.iter_mut().find(|worker| worker.get_id() == id){
+            Some(worker) => {
+                worker.set_absent(true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the holidays of the coming tact from the hours worked on each of its days.
+    pub fn plan_holidays(&mut self, holidays: &HolidayPlan, days: &[(NaiveDate, f64)]){
+        let total_hours: f64 = days.iter().map(|(_, hours)| hours).sum();
+        if total_hours <= 0.0{
+            return;
+        }
+        for worker in self.workers.iter_mut(){
+            let off_hours: f64 = days.iter()
+                .filter(|(date, _)| holidays.is_worker_off(worker.get_id(), &self.name, *date))
+                .map(|(_, hours)| hours)
+                .sum();
+            worker.set_holiday_share(off_hours / total_hours);
+        }
+        self.holiday_headcount = days.iter()
+            .map(|(date, hours)| holidays.get_headcount_off(&self.name, *date) as f64 * hours)
+            .sum::<f64>() / total_hours;
+    }
+
+    /// Starts a tact worked as a single slice with the given line hours, see open_tact and start_slice.
+    #[cfg(test)]
+    pub fn start_tact(&mut self, line_hours: f64) -> f64{
+        self.open_tact();
+        self.start_slice(line_hours, None)
+    }
+
+    /// Starts a tact, which is then worked in slices between the events in it.
+    pub fn open_tact(&mut self){
+        self.tact_present = vec![0.0; self.workers.len()];
+        self.tact_worked = vec![0.0; self.workers.len()];
+        self.tact_output = 0.0;
+        self.tact_capacity = 0.0;
+        self.tact_available = 0.0;
+        self.lent.push(0.0);
+        self.borrowed.push(0.0);
+        self.blocked.push(0.0);
+        self.overtime_hours.push(0.0);
+    }
+
+    /// Part of a slice the shift is on: with the shifts that are on right now all or nothing,
+    /// without a shift timetable the average share. A worker without a shift works the SOT's.
+    fn get_slice_share(&self, worker: Option<&Worker>, active: Option<&[String]>) -> f64{
+        let on = |on: bool| if on { 1.0 } else { 0.0 };
+        match (active, worker.and_then(|worker| worker.get_shift())){
+            (None, _) => worker.and_then(|worker| worker.get_shift_share()).unwrap_or(self.shift_share),
+            (Some(active), Some(shift)) => on(active.contains(shift)),
+            (Some(active), None) if self.shifts.is_empty() => on(!active.is_empty()),
+            (Some(active), None) => on(self.shifts.iter().any(|shift| active.contains(shift))),
+        }
+    }
+
+    /// Works out the hours of every present worker for a slice of the tact with the given line hours
+    /// and the shifts that are on in it, None without a shift timetable.
+    /// Returns the SOT's available time, which every worker then spends in work().
+    pub fn start_slice(&mut self, line_hours: f64, active: Option<&[String]>) -> f64{
+        let shares: Vec<f64> = self.workers.iter().map(|worker| self.get_slice_share(Some(worker), active)).collect();
+        let available_time = match active{
+            None => self.get_available_time(line_hours),
+            // Workers on another shift than their SOT are available in theirs
+            Some(_) => line_hours * self.workers.iter().zip(&shares)
+                .filter(|(worker, _)| worker.is_employed())
+                .map(|(_, share)| *share)
+                .fold(self.get_slice_share(None, active), f64::max),
+        };
+        // Workers off by headcount are not named, all workers lose the same part of their hours
+        let headcount_share = match self.workers.iter().filter(|worker| worker.is_employed()).count(){
+            0 => 1.0,
+            n => (1.0 - self.holiday_headcount / n as f64).max(0.0),
+        };
+        self.present_hours = self.workers.iter().zip(&shares)
+            .map(|(worker, share)| match worker.is_absent() || !worker.is_employed(){
+                true => 0.0,
+                false => line_hours * share * (1.0 - worker.get_holiday_share()) * headcount_share,
+            })
+            .collect();
+        self.worker_rates = self.workers.iter().zip(&self.present_hours)
+            .map(|(worker, hours)| match available_time > 0.0{
+                true => hours * worker.get_efficiency() * self.capacity.get_rate() / available_time,
+                false => 0.0,
+            })
+            .collect();
+        let scheduled: f64 = self.workers.iter().zip(&shares)
+            .filter(|(worker, _)| worker.is_employed())
+            .map(|(_, share)| line_hours * share)
+            .sum();
+        let weighted: f64 = self.workers.iter().zip(&self.present_hours).map(|(worker, hours)| hours * worker.get_efficiency()).sum();
+        self.waterfall.add_tact(&self.capacity, scheduled, self.present_hours.iter().sum(), weighted);
+        self.worker_time = vec![available_time; self.workers.len()];
+        self.available_time = available_time;
+        self.station_budgets.clear();
+        available_time
+    }
+
+    /// Ends the slice started with start_slice and adds its hours to the tact.
+    pub fn end_slice(&mut self){
+        self.tact_present.resize(self.workers.len(), 0.0);
+        self.tact_worked.resize(self.workers.len(), 0.0);
+        if self.available_time > 0.0{
+            for i in 0..self.present_hours.len(){
+                self.tact_present[i] += self.present_hours[i];
+                self.tact_worked[i] += self.present_hours[i] * (1.0 - self.worker_time[i] / self.available_time);
+                self.tact_output += self.worker_rates[i] * (self.available_time - self.worker_time[i]);
+                self.tact_capacity += self.worker_rates[i] * self.available_time;
+            }
+        }
+        self.tact_available += self.available_time;
+        self.available_time = 0.0;
+        self.present_hours.clear();
+        self.worker_rates.clear();
+        self.worker_time.clear();
+    }
+
+    /// Ends the tact: books the blocked share, the utilization and the hours every worker worked.
+    pub fn end_tact(&mut self){
+        self.end_slice();
+        if let Some(blocked) = self.blocked.last_mut(){
+            *blocked = match self.tact_available > 0.0{
+                true => *blocked / self.tact_available,
+                false => 0.0,
+            };
+        }
+        let utilization = match self.tact_capacity > 0.0{
+            true => self.tact_output / self.tact_capacity,
+            false => 0.0,
+        };
+        self.add_utilization(utilization);
+    }
+
+    /// Productive hours per hour of available time of all workers together in the current slice.
+    pub fn get_rate(&self) -> f64{
+        self.worker_rates.iter().sum()
+    }
+
+    /// Available time left in the current slice, averaged over the workers by their rates.
+    pub fn get_remaining_time(&self) -> f64{
+        let rate: f64 = self.worker_rates.iter().sum();
+        if rate <= 0.0{
+            return self.available_time;
+        }
+        self.worker_rates.iter().zip(&self.worker_time).map(|(rate, time)| rate * time).sum::<f64>() / rate
+    }
+
     pub fn get_name(&self) -> String{
         self.name.clone()
     }
@@ -27,29 +364,328 @@ impl SOT{
         self.station_assignment.clone()
     }
 
-    pub fn work(&mut self, tasklist: Vec<&mut Task>, tacttime: f64) -> f64{
-        let mut available_time = tacttime;
-        //Try to set priority on tasks
-        for task in tasklist{
-            if available_time > 0.0 {
-                available_time = task.work_and_get_remaining_time(available_time, self.workers, self.cvat);
-            }
-            else{
-                break;
+    /// Works on the tasks in order with the time left in the current slice and returns the remaining time.
+    /// Only workers holding a task's qualifications work on it, the others keep their time for later tasks.
+    pub fn work(&mut self, tasklist: Vec<&mut Task>) -> f64{
+        self.spend(tasklist, 1.0, None);
+        self.get_remaining_time()
+    }
+
+    /// Same as work, for tasks of a station that is open for the given share of the slice.
+    /// Each worker spends at most that share of the available time on the station.
+    pub fn work_at_station(&mut self, tasklist: Vec<&mut Task>, station_id: usize, open_share: f64) -> f64{
+        self.spend(tasklist, 1.0, Some((station_id, open_share)));
+        self.get_remaining_time()
+    }
+
+    /// Works on another SOT's tasks at a station with the time left, at the given lending efficiency.
+    /// Returns the hours the workers spent on them.
+    pub fn lend(&mut self, tasklist: Vec<&mut Task>, efficiency: f64, station_id: usize, open_share: f64) -> f64{
+        let hours = self.spend(tasklist, efficiency, Some((station_id, open_share)));
+        if let Some(lent) = self.lent.last_mut(){
+            *lent += hours;
+        }
+        hours
+    }
+
+    pub fn add_borrowed(&mut self, hours: f64){
+        if let Some(borrowed) = self.borrowed.last_mut(){
+            *borrowed += hours;
+        }
+    }
+
+    pub fn get_lent(&self) -> &Vec<f64>{
+        &self.lent
+    }
+
+    pub fn get_borrowed(&self) -> &Vec<f64>{
+        &self.borrowed
+    }
+
+    /// Books available time of the current slice as blocked, it becomes a share of the tact's
+    /// available time when the tact ends.
+    pub fn add_blocked(&mut self, time: f64){
+        if let Some(blocked) = self.blocked.last_mut(){
+            *blocked += time;
+        }
+    }
+
+    pub fn get_blocked(&self) -> &Vec<f64>{
+        &self.blocked
+    }
+
+    pub fn set_overtime(&mut self, overtime: OvertimePolicy){
+        self.overtime = Some(overtime);
+    }
+
+    pub fn get_overtime(&self) -> Option<&OvertimePolicy>{
+        self.overtime.as_ref()
+    }
+
+    /// Resets the overtime hours counted against the weekly limit.
+    pub fn start_week(&mut self){
+        self.week_overtime = 0.0;
+    }
+
+    /// Works overtime on the tasks due at the SOT's stations if their workload is above the
+    /// policy's threshold. Tasks come per station with the station's open share, each worker spends
+    /// at most that share of the overtime on a station that is partly down.
+    /// Returns the overtime worker hours, only the hours needed are worked.
+    pub fn work_overtime(&mut self, stations: Vec<(f64, Vec<&mut Task>)>, due_workload: f64) -> f64{
+        let hours = match &self.overtime{
+            Some(overtime) => overtime.get_hours(due_workload, self.week_overtime),
+            None => return 0.0,
+        };
+        // Overtime comes after the slices of the tact
+        self.end_slice();
+        let present: Vec<bool> = self.tact_present.iter().map(|hours| *hours > 0.0).collect();
+        let present_count = present.iter().filter(|present| **present).count();
+        if hours <= 0.0 || present_count == 0{
+            return 0.0;
+        }
+        let rates: Vec<f64> = self.workers.iter().map(|worker| worker.get_efficiency() * self.capacity.get_productivity()).collect();
+        let mut times: Vec<f64> = present.iter().map(|present| if *present { hours } else { 0.0 }).collect();
+        for (open_share, tasklist) in stations{
+            let mut budget = (open_share < 1.0).then(|| vec![open_share.max(0.0) * hours; self.workers.len()]);
+            spend_time(&self.workers, &rates, &mut times, tasklist, 1.0, budget.as_mut(), None);
+        }
+
+        let worked: f64 = present.iter().zip(&times)
+            .filter(|(present, _)| **present)
+            .map(|(_, time)| hours - time)
+            .sum();
+        self.week_overtime += worked / present_count as f64;
+        if let Some(overtime_hours) = self.overtime_hours.last_mut(){
+            *overtime_hours += worked;
+        }
+        worked
+    }
+
+    /// Overtime worker hours per tact.
+    pub fn get_overtime_hours(&self) -> &Vec<f64>{
+        &self.overtime_hours
+    }
+
+    pub fn get_overtime_cost(&self) -> f64{
+        match &self.overtime{
+            Some(overtime) => self.overtime_hours.iter().sum::<f64>() * overtime.cost_per_hour,
+            None => 0.0,
+        }
+    }
+
+    /// Spends the workers' time left on the tasks in order, returns the present hours used.
+    /// Tasks of a station that is partly down are limited by the station's budget.
+    fn spend(&mut self, tasklist: Vec<&mut Task>, efficiency: f64, station: Option<(usize, f64)>) -> f64{
+        let time_before: f64 = self.get_worked_hours();
+        let learning = match station.filter(|(_, open_share)| *open_share < 1.0){
+            Some((station_id, open_share)) => {
+                let limit = open_share.max(0.0) * self.available_time;
+                let mut budget = self.station_budgets.remove(&station_id).unwrap_or_else(|| vec![limit; self.workers.len()]);
+                let learning = spend_time(&self.workers, &self.worker_rates, &mut self.worker_time, tasklist, efficiency, Some(&mut budget), Some(self.available_time));
+                self.station_budgets.insert(station_id, budget);
+                learning
             }
+            None => spend_time(&self.workers, &self.worker_rates, &mut self.worker_time, tasklist, efficiency, None, Some(self.available_time)),
+        };
+        self.waterfall.learning += learning;
+        self.get_worked_hours() - time_before
+    }
+
+    /// Hours the present workers have worked so far in the current slice.
+    fn get_worked_hours(&self) -> f64{
+        if self.available_time <= 0.0{
+            return 0.0;
         }
-        available_time
+        self.present_hours.iter().zip(&self.worker_time)
+            .map(|(hours, time)| hours * (1.0 - time / self.available_time))
+            .sum()
     }
 
     pub fn get_utilization(&self) -> Vec<f64>{
         self.utilization.clone()
     }
 
+    /// Records the utilization of a tact and books the hours every worker spent working.
     pub fn add_utilization(&mut self, util: f64){
+        self.end_slice();
         self.utilization.push(util);
+        for (worker, hours) in self.workers.iter_mut().zip(&self.tact_worked){
+            worker.add_hours(*hours);
+        }
+    }
+}
+
+/// Works on the tasks in order. Each worker has a rate in productive hours per hour and a time left,
+/// only workers holding a task's qualifications work on it and they spend the same share of their time.
+/// With budgets, a worker spends at most its budget, which is reduced by the time spent.
+/// A finished task gets the share of the slice its workers had used up by then, out of the available
+/// time the times count down from; without one, e.g. in overtime, it is finished at the end of the tact.
+/// Returns the productive hours that went into the extra workload of the learning curve.
+fn spend_time(workers: &[Worker], rates: &[f64], times: &mut [f64], tasklist: Vec<&mut Task>, efficiency: f64, mut budgets: Option<&mut Vec<f64>>, available_time: Option<f64>) -> f64{
+    let mut learning = 0.0;
+    // Tasks come in the order of the SOT's dispatch rule
+    for task in tasklist{
+        if times.iter().all(|time| *time <= 0.0){
+            break;
+        }
+        let usable: Vec<f64> = match budgets.as_deref(){
+            Some(budgets) => times.iter().zip(budgets).map(|(time, budget)| time.min(*budget)).collect(),
+            None => times.to_vec(),
+        };
+        let qualified: Vec<usize> = (0..workers.len())
+            .filter(|i| usable[*i] > 0.0 && workers[*i].is_qualified(task.get_qualifications()))
+            .collect();
+        let capacity: f64 = qualified.iter().map(|i| rates[*i] * efficiency * usable[*i]).sum();
+        if capacity <= 0.0{
+            continue;
+        }
+        // All qualified workers spend the same share of their usable time
+        let workload_before = task.get_workload();
+        let remaining_share = task.work_and_get_remaining_time(1.0, capacity);
+        learning += (workload_before - task.get_workload()) * (1.0 - 1.0 / task.get_learning_factor());
+        for &i in qualified.iter(){
+            match budgets.as_deref_mut(){
+                Some(budgets) => {
+                    let spent = usable[i] * (1.0 - remaining_share);
+                    times[i] -= spent;
+                    budgets[i] -= spent;
+                }
+                None => times[i] *= remaining_share,
+            }
+        }
+        if workload_before > 0.0 && task.get_workload() <= 0.0{
+            let share = match available_time.filter(|time| *time > 0.0){
+                Some(available_time) => qualified.iter().map(|i| 1.0 - times[*i] / available_time).fold(0.0, f64::max).min(1.0),
+                None => 1.0,
+            };
+            task.set_finish_share(share);
+        }
     }
+    learning
 }
 
 #[cfg(test)]
 mod tests{
+    use super::*;
+
+    #[test]
+    fn night_shift_only_sot_gets_part_of_tact(){
+        let mut sot = SOT::new("NDT", vec!["S1".to_string()], 2, 1.0);
+        assert_eq!(sot.get_available_time(9.0), 9.0);
+
+        sot.set_shifts(vec!["night".to_string()], 1.0 / 3.0);
+        assert!((sot.get_available_time(9.0) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rate_from_present_workers(){
+        let mut sot = SOT::new("MECH", vec!["S1".to_string()], 2, 0.5);
+        sot.set_shifts(vec!["early".to_string()], 0.5);
+        let mut late = Worker::new("W2", "MECH", 1.0);
+        late.set_shift("late", 0.5);
+        sot.set_workers(vec![Worker::new("W1", "MECH", 0.8), late]);
+
+        // 10h of line time: 5h available, W1 and W2 each present 5h
+        assert_eq!(sot.start_tact(10.0), 5.0);
+        let mut task = Task::new(100.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string());
+        sot.work(vec![&mut task]);
+        assert!((task.get_workload() - (100.0 - 5.0 * 0.8 * 0.5 - 5.0 * 0.5)).abs() < 1e-9);
+
+        sot.add_utilization(1.0);
+        assert_eq!(sot.get_workers()[1].get_hours(), &vec![5.0]);
+    }
+
+    #[test]
+    fn lent_hours_work_at_lending_efficiency(){
+        let mut sot = SOT::new("MECH", vec!["S1".to_string()], 2, 1.0);
+        sot.start_tact(10.0);
+        let mut task = Task::new(8.0, vec!["S1".to_string()], "ELEC".to_string(), "A".to_string());
+
+        assert_eq!(sot.lend(vec![&mut task], 0.8, 0, 1.0), 10.0);
+        assert!((sot.get_remaining_time() - 5.0).abs() < 1e-9);
+        assert_eq!(sot.get_lent(), &vec![10.0]);
+    }
+
+    #[test]
+    fn overtime_only_as_needed(){
+        let mut sot = SOT::new("MECH", vec!["S1".to_string()], 2, 0.5);
+        sot.set_overtime(OvertimePolicy { max_per_tact: 4.0, max_per_week: None, threshold: 0.0, cost_per_hour: 60.0 });
+        sot.start_tact(10.0);
+        let mut task = Task::new(2.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string());
+
+        // Two workers at cvat 0.5 need 2h each for 2h of workload
+        assert!((sot.work_overtime(vec![(1.0, vec![&mut task])], 2.0) - 4.0).abs() < 1e-9);
+        assert_eq!(task.get_workload(), 0.0);
+        assert!((sot.get_overtime_cost() - 240.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn overtime_keeps_to_station_downtime(){
+        let mut sot = SOT::new("MECH", vec!["S1".to_string(), "S2".to_string()], 1, 1.0);
+        sot.set_overtime(OvertimePolicy { max_per_tact: 4.0, max_per_week: None, threshold: 0.0, cost_per_hour: 60.0 });
+        sot.start_tact(10.0);
+        let mut down = Task::new(3.0, vec!["S2".to_string()], "MECH".to_string(), "A".to_string());
+        let mut half = Task::new(3.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string());
+
+        // Nothing on the closed station, half of the 4 hours on the one down for half the tact
+        let worked = sot.work_overtime(vec![(0.0, vec![&mut down]), (0.5, vec![&mut half])], 6.0);
+        assert!((worked - 2.0).abs() < 1e-9);
+        assert_eq!(down.get_workload(), 3.0);
+        assert!((half.get_workload() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn staffing_hires_and_releases_workers(){
+        let mut sot = SOT::new("MECH", vec!["S1".to_string()], 2, 1.0);
+        sot.set_staffing(vec![
+            StaffingChange { tact: 2, bc: 1, cvat: None },
+            StaffingChange { tact: 1, bc: 3, cvat: Some(0.5) },
+        ]);
+        let mut available = vec![];
+        for tact in 0..3{
+            sot.apply_staffing(tact);
+            sot.start_tact(10.0);
+            let mut task = Task::new(100.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string());
+            sot.work(vec![&mut task]);
+            available.push(100.0 - task.get_workload());
+            sot.add_utilization(1.0);
+        }
+        assert_eq!(available, vec![20.0, 15.0, 5.0]);
+        assert_eq!(sot.get_workers()[2].get_hours(), &vec![0.0, 10.0, 0.0]);
+    }
+
+    #[test]
+    fn station_down_limits_work_there(){
+        let mut sot = SOT::new("MECH", vec!["S1".to_string(), "S2".to_string()], 1, 1.0);
+        sot.start_tact(10.0);
+        let mut blocked = Task::new(8.0, vec!["S2".to_string()], "MECH".to_string(), "A".to_string());
+        let mut open = Task::new(8.0, vec!["S1".to_string()], "MECH".to_string(), "A".to_string());
+
+        // S2 is down for 7 of the 10 hours, the rest of the time goes to S1
+        sot.work_at_station(vec![&mut blocked], 1, 0.3);
+        assert!((blocked.get_workload() - 5.0).abs() < 1e-9);
+        sot.work_at_station(vec![&mut open], 0, 1.0);
+        assert!((open.get_workload() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn only_qualified_workers_work_on_task(){
+        let mut sot = SOT::new("ELEC", vec!["S1".to_string()], 0, 1.0);
+        let mut certified = Worker::new("W1", "ELEC", 1.0);
+        certified.set_qualifications(vec!["SIGNOFF".to_string()]);
+        sot.set_workers(vec![certified, Worker::new("W2", "ELEC", 1.0)]);
+        sot.start_tact(10.0);
+
+        let mut signoff = Task::new(30.0, vec!["S1".to_string()], "ELEC".to_string(), "A".to_string());
+        signoff.set_qualifications(vec!["SIGNOFF".to_string()]);
+        let mut wiring = Task::new(5.0, vec!["S1".to_string()], "ELEC".to_string(), "A".to_string());
+        let remaining = sot.work(vec![&mut signoff, &mut wiring]);
+
+        // W1 spends the tact on the sign-off, W2 finishes the wiring and idles for the rest
+        assert_eq!(signoff.get_workload(), 20.0);
+        assert_eq!(wiring.get_workload(), 0.0);
+        assert!((remaining - 2.5).abs() < 1e-9);
+        sot.add_utilization(1.0 - remaining / 10.0);
+        assert_eq!(sot.get_workers()[1].get_hours(), &vec![5.0]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+/// Headcount (and optionally cvat) of a SOT from a tact on, from staffing.csv.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaffingChange{
+    pub tact: usize,
+    pub bc: u16,
+    // New cvat of the SOT, unchanged if None
+    pub cvat: Option<f64>,
+}
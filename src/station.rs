@@ -62,6 +62,7 @@ impl fmt::Display for Station{
 #[cfg(test)]
 mod tets{
     use crate::util;
+    use crate::datasource::fixture;
 
     use super::*;
 
@@ -70,7 +71,7 @@ mod tets{
         let mut this_station = Station::new("B");
         let mut previous_station = Station::new("A");
 
-        let msn = util::get_tactplan()[0].to_owned();
+        let msn = util::get_tactplan(&fixture(), "1").unwrap()[0].to_owned();
         previous_station.set_current_msn(msn);
 
         //Trying to tact from A->B
@@ -85,7 +86,7 @@ mod tets{
         let mut this_station = Station::new("B");
         let mut previous_station = Station::new("A");
 
-        let msn = util::get_tactplan()[0].to_owned();
+        let msn = util::get_tactplan(&fixture(), "1").unwrap()[0].to_owned();
         let workload = msn.get_workload();
 
         previous_station.set_current_msn(msn);
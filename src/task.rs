@@ -1,42 +1,63 @@
 use serde::Deserialize;
+use std::sync::Arc;
 
+// What tasks.csv says about a task besides its workload, shared by the copies of the task in
+// every MSN of the version
 #[derive(Debug, Clone)]
-pub struct Task{
-    workload: f64,
+struct TaskInfo{
     stations: Vec<String>,
     sot: String,
-    version: String
+    version: String,
+    // Qualifications a worker needs to work on the task
+    qualifications: Vec<String>,
+    // Id within the version, only needed to be a predecessor
+    id: Option<String>,
+    // Ids of the tasks of the same version that have to be finished before this one starts
+    predecessors: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Task{
+    workload: f64,
+    // Workload the planning expected, the actual workload if not predicted
+    predicted_workload: Option<f64>,
+    info: Arc<TaskInfo>,
+    // Workload relative to tasks.csv after the learning curve
+    learning_factor: f64,
+    // Completion was reported, see check_completed
+    completed: bool,
+    // Share of the tact that had passed when the workload was done
+    finish_share: f64,
 }
 
 impl Task{
     pub fn new(workload: f64, stations: Vec<String>, sot: String, version: String) -> Task{
         Task{
             workload,
-            stations,
-            sot,
-            version,
+            predicted_workload: None,
+            info: Arc::new(TaskInfo { stations, sot, version, qualifications: vec![], id: None, predecessors: vec![] }),
+            learning_factor: 1.0,
+            completed: false,
+            finish_share: 0.0,
         }
     }
 
-    pub fn work_and_get_remaining_time(&mut self, available_time: f64, bluecollar: u16, cvat: f64) -> f64{
+    /// Works on the task with the SOT's rate in productive hours per hour (present workers * efficiency * cvat).
+    pub fn work_and_get_remaining_time(&mut self, available_time: f64, rate: f64) -> f64{
         // println!("Initial Workload: {}", self.workload);
-        // println!("Blue Collars: {}", self.bluecollar);
-        // println!("CVAT: {}", self.cvat);
+        // println!("Rate: {}", rate);
         // println!("Available time: {}", available_time);
 
-        // Get Available Workers with CVAT
-        let available_workers = bluecollar as f64 * cvat;
-
-        // Recalculate Task Workload by subtracting workers*time
-        self.workload -= available_workers * available_time;
+        // Recalculate Task Workload by subtracting rate*time
+        self.workload -= rate * available_time;
 
         //println!("Final Workload: {}", self.workload);
 
         //If workload goes below 0, set workload to 0 and return the
-        // negative balance divided by the number of workers to get the remaining time
+        // negative balance divided by the rate to get the remaining time
         if self.workload < 0.0{
             //println!("Workload complete, but available time not burned.\n");
-            let remaining_time = -self.workload/available_workers;
+            let remaining_time = -self.workload/rate;
             self.workload = 0.0;
             remaining_time
         } 
@@ -46,20 +67,96 @@ impl Task{
         }
     }
 
+    /// Scales the workload, e.g. by the learning factor of an early unit.
+    pub fn scale_workload(&mut self, factor: f64){
+        self.workload *= factor;
+        self.learning_factor *= factor;
+    }
+
+    pub fn get_learning_factor(&self) -> f64{
+        self.learning_factor
+    }
+
+    pub fn set_finish_share(&mut self, share: f64){
+        self.finish_share = share;
+    }
+
+    pub fn get_finish_share(&self) -> f64{
+        self.finish_share
+    }
+
+    /// True the first time it is called after the workload is done.
+    pub fn check_completed(&mut self) -> bool{
+        if self.completed || self.workload > 0.0{
+            return false;
+        }
+        self.completed = true;
+        true
+    }
+
+    /// A task may run over several stations. Its workload is one pool that can be worked on
+    /// while the MSN is at any of them; whatever is left after the last one travels as OSW.
+    pub fn is_last_station(&self, station: &str) -> bool{
+        self.info.stations.last().map(|s| s == station).unwrap_or(false)
+    }
+
     pub fn get_stations(&self) -> Vec<String>{
-        self.stations.clone()
+        self.info.stations.clone()
+    }
+
+    pub fn get_station_refs(&self) -> &Vec<String>{
+        &self.info.stations
     }
 
     pub fn get_workload(&self) -> f64{
         self.workload
     }
 
+    pub fn set_predicted_workload(&mut self, predicted_workload: Option<f64>){
+        self.predicted_workload = predicted_workload;
+    }
+
+    pub fn get_predicted_workload(&self) -> Option<f64>{
+        self.predicted_workload
+    }
+
+    /// Replaces the workload by the predicted one, if there is one.
+    pub fn use_predicted_workload(&mut self){
+        if let Some(predicted_workload) = self.predicted_workload{
+            self.workload = predicted_workload;
+        }
+    }
+
     pub fn get_version(&self) -> &str{
-        &self.version
+        &self.info.version
+    }
+
+    pub fn set_qualifications(&mut self, qualifications: Vec<String>){
+        Arc::make_mut(&mut self.info).qualifications = qualifications;
+    }
+
+    pub fn set_id(&mut self, id: Option<String>){
+        Arc::make_mut(&mut self.info).id = id;
+    }
+
+    pub fn get_id(&self) -> Option<&str>{
+        self.info.id.as_deref()
+    }
+
+    pub fn set_predecessors(&mut self, predecessors: Vec<String>){
+        Arc::make_mut(&mut self.info).predecessors = predecessors;
+    }
+
+    pub fn get_predecessors(&self) -> &Vec<String>{
+        &self.info.predecessors
+    }
+
+    pub fn get_qualifications(&self) -> &Vec<String>{
+        &self.info.qualifications
     }
 
     pub fn get_sot(&self) -> &String{
-        &self.sot
+        &self.info.sot
     }
 }
 
@@ -70,12 +167,19 @@ mod tests{
     #[test]
     fn test_work_and_get_remaining_time(){
         let mut task = Task::new(100.0, vec!["Station1".to_string(), "Station2".to_string()], "SOT1".to_string(), "Version1".to_string());
-        let mut remaining_time = task.work_and_get_remaining_time(10.0, 1, 1.);
+        let mut remaining_time = task.work_and_get_remaining_time(10.0, 1.);
         //Tact 9 times
         for _ in 0..9{
-            remaining_time = task.work_and_get_remaining_time(10.0, 1, 1.);
+            remaining_time = task.work_and_get_remaining_time(10.0, 1.);
         }
         assert_eq!(remaining_time, 0.0);
         assert_eq!(task.get_workload(), 0.0);
     }
+
+    #[test]
+    fn last_station_of_multi_station_task(){
+        let task = Task::new(10.0, vec!["S1".to_string(), "S2".to_string()], "SOT1".to_string(), "Version1".to_string());
+        assert!(!task.is_last_station("S1"));
+        assert!(task.is_last_station("S2"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens to the work an MSN still has when it leaves the last station.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitPolicy{
+    // The MSN leaves with its OSW, which is only reported
+    Sink,
+    // A rework area next to the line works the OSW off with its own capacity
+    Rework,
+    // The MSN is moved to a delivery centre, which works the OSW off with its own capacity
+    DeliveryCentre,
+    // The MSN stays at the last station and stops the line until its work is done
+    Hold,
+}
+
+impl ExitPolicy{
+    pub fn parse(policy: &str) -> Option<Self>{
+        match policy{
+            "sink" => Some(ExitPolicy::Sink),
+            "rework" => Some(ExitPolicy::Rework),
+            "delivery_centre" | "delivery-centre" => Some(ExitPolicy::DeliveryCentre),
+            "hold" => Some(ExitPolicy::Hold),
+            _ => None,
+        }
+    }
+}
+
+/// Handling of unfinished work at line exit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnfinishedWork{
+    pub policy: ExitPolicy,
+    // Productive hours per tact of the rework area or the delivery centre
+    pub capacity: f64,
+    // Hours to move an MSN to the delivery centre before work starts there
+    pub transfer_hours: f64,
+    // Tacts in a row an MSN may be held before the run stops with an error
+    pub max_held_tacts: usize,
+}
+
+impl Default for UnfinishedWork{
+    fn default() -> Self{
+        Self { policy: ExitPolicy::Sink, capacity: 0.0, transfer_hours: 0.0, max_held_tacts: 1000 }
+    }
+}
+
+impl UnfinishedWork{
+    /// Whether OSW is worked off outside the line, which needs a capacity.
+    pub fn has_area(&self) -> bool{
+        matches!(self.policy, ExitPolicy::Rework | ExitPolicy::DeliveryCentre)
+    }
+}
+
+/// When an MSN was due to leave the line, when it left and when it was delivered, in line hours.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery{
+    pub msn: u32,
+    // Work left when the MSN left the line
+    pub osw: f64,
+    pub planned_exit: f64,
+    pub exit: f64,
+    pub delivery: f64,
+}
+
+impl Delivery{
+    /// Hours the MSN was delivered after its planned line exit.
+    pub fn get_delay(&self) -> f64{
+        self.delivery - self.planned_exit
+    }
+}
+
+/// Rework area or delivery centre: works off the OSW of the MSNs one after another in the order
+/// they arrive. MSNs without OSW are delivered at line exit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitArea{
+    // Productive hours per line hour
+    rate: f64,
+    transfer_hours: f64,
+    // Line hour at which the area finished the last MSN
+    free_at: f64,
+}
+
+impl ExitArea{
+    pub fn new(unfinished: &UnfinishedWork, tact_time: f64) -> Self{
+        let transfer_hours = match unfinished.policy{
+            ExitPolicy::DeliveryCentre => unfinished.transfer_hours,
+            _ => 0.0,
+        };
+        Self { rate: unfinished.capacity / tact_time, transfer_hours, free_at: 0.0 }
+    }
+
+    /// Line hour at which an MSN that left the line at exit with the given OSW is delivered.
+    pub fn deliver(&mut self, osw: f64, exit: f64) -> f64{
+        if osw <= 0.0{
+            return exit;
+        }
+        let start = (exit + self.transfer_hours).max(self.free_at);
+        self.free_at = start + osw / self.rate;
+        self.free_at
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn area_works_off_osw_in_order(){
+        let unfinished = UnfinishedWork { policy: ExitPolicy::DeliveryCentre, capacity: 20.0, transfer_hours: 5.0, ..UnfinishedWork::default() };
+        let mut area = ExitArea::new(&unfinished, 10.0);
+        // 2 productive hours per line hour, after 5 hours of transfer
+        assert_eq!(area.deliver(10.0, 40.0), 50.0);
+        // Waits for the MSN before
+        assert_eq!(area.deliver(4.0, 42.0), 52.0);
+        assert_eq!(area.deliver(0.0, 44.0), 44.0);
+        assert_eq!(ExitPolicy::parse("delivery-centre"), Some(ExitPolicy::DeliveryCentre));
+    }
+}
@@ -1,62 +1,252 @@
-use std::fs::File;
+use std::io::Read;
+use std::collections::{HashMap, VecDeque};
 use indexmap::IndexMap;
 use csv::StringRecord;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use crate::task::Task;
 use crate::msn::MSN;
 use crate::station::Station;
 use crate::sot::SOT;
+use crate::hangar::Hangar;
+use crate::intern::Interner;
+use crate::shift::ShiftSystem;
+use crate::calendar::{CalendarException, ExceptionKind};
+use crate::worker::Worker;
+use crate::absence::{SickLeave, SickLeaveRate};
+use crate::holiday::{Holiday, HolidayPlan};
+use crate::learning::{Learning, LearningCurve};
+use crate::overtime::OvertimePolicy;
+use crate::staffing::StaffingChange;
+use crate::downtime::Downtime;
+use crate::capacity::{CapacityFactors, CapacityWaterfall};
+use crate::dispatch::Dispatch;
+use crate::engine::Event;
+use crate::unfinished::Delivery;
+use crate::comparison::WorkloadComparison;
+use crate::calendar::Calendar;
+use crate::settings::LearningSettings;
+use chrono::NaiveDate;
+use crate::error::PpeError;
+use crate::datasource::{DataSource, InputFile, OutputSink};
 
 
 #[derive(Debug, Deserialize)]
-struct TaskReadIn{
-    station: String,
-    sot: String,
-    version: String,
-    workload: f64,
+pub(crate) struct TaskReadIn{
+    pub(crate) station: String,
+    pub(crate) sot: String,
+    pub(crate) version: String,
+    pub(crate) workload: f64,
+    // Workload the planning expected, workload is the actual one
+    #[serde(default)]
+    pub(crate) predicted_workload: Option<f64>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+    // Separated by ";", any worker of the SOT if empty
+    #[serde(default)]
+    pub(crate) qualifications: Option<String>,
+    // Unique within the version
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    // Ids of tasks of the same version, separated by ";", that have to be finished first
+    #[serde(default)]
+    pub(crate) predecessors: Option<String>,
 }
 
-fn read_tasks() -> Vec<Task>{
-    let file_path = "data/tasks.csv";
-    let file = File::open(file_path).expect("Failed to open file");
+// Hangar used when the input data has no hangar column and no hangar was requested
+pub const DEFAULT_HANGAR: &str = "1";
 
-    let mut rdr = csv::Reader::from_reader(file);
+/// Rows without a hangar belong to every hangar.
+pub(crate) fn in_hangar(row_hangar: &Option<String>, hangar: &str) -> bool{
+    match row_hangar{
+        Some(row_hangar) if !row_hangar.is_empty() => row_hangar == hangar,
+        _ => true,
+    }
+}
+
+// A csv reader over an input file with the file's headers
+type CsvReader<'a> = (csv::Reader<Box<dyn Read + 'a>>, StringRecord);
+
+fn open_reader<'a>(source: &'a DataSource, file: InputFile) -> Result<CsvReader<'a>, PpeError>{
+    csv_reader(source, file, source.open(file)?)
+}
+
+/// Opens an optional input. Returns None if it is not configured or not in the data directory,
+/// any other error opening or reading it is returned.
+fn open_optional_reader<'a>(source: &'a DataSource, file: InputFile) -> Result<Option<CsvReader<'a>>, PpeError>{
+    source.open_optional(file)?.map(|data| csv_reader(source, file, data)).transpose()
+}
+
+fn csv_reader<'a>(source: &DataSource, file: InputFile, data: Box<dyn Read + 'a>) -> Result<CsvReader<'a>, PpeError>{
+    let mut rdr = csv::Reader::from_reader(data);
+    let headers = rdr.headers().map_err(|e| PpeError::bad_row(&source.describe(file), e, None))?.clone();
+    Ok((rdr, headers))
+}
+
+/// A parsed row together with its line in the file, used where bad rows must not stop reading.
+pub(crate) struct Row<T>{
+    pub(crate) line: u64,
+    pub(crate) value: Result<T, PpeError>,
+}
+
+pub(crate) fn read_table<T: DeserializeOwned>(source: &DataSource, file: InputFile) -> Result<Vec<Row<T>>, PpeError>{
+    let file_path = source.describe(file);
+    let (mut rdr, headers) = open_reader(source, file)?;
+
+    let mut rows = vec![];
+    for record in rdr.records(){
+        let row = match record{
+            Ok(record) => Row{
+                line: record.position().map(|pos| pos.line()).unwrap_or(0),
+                value: record.deserialize(Some(&headers)).map_err(|e| PpeError::bad_row(&file_path, e, Some(&headers))),
+            },
+            Err(e) => Row{
+                line: e.position().map(|pos| pos.line()).unwrap_or(0),
+                value: Err(PpeError::bad_row(&file_path, e, Some(&headers))),
+            },
+        };
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn finish_writer(wtr: csv::Writer<Vec<u8>>, file_name: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    let data = wtr.into_inner().map_err(|e| PpeError::output(file_name, e))?;
+    sink.write(file_name, data)
+}
+
+pub fn read_tasks(source: &DataSource, hangar: &str) -> Result<Vec<Task>, PpeError>{
+    let file_path = &source.describe(InputFile::Tasks);
+    let (mut rdr, headers) = open_reader(source, InputFile::Tasks)?;
 
     let mut task_table = vec![];
-    for line in rdr.deserialize(){
-        let res: TaskReadIn = line.expect("Failed to read Data");
-        task_table.push(res);
+    let mut lines = vec![];
+    for (i, line) in rdr.deserialize().enumerate(){
+        let res: TaskReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if in_hangar(&res.hangar, hangar){
+            task_table.push(res);
+            lines.push(i as u64 + 2);
+        }
+    }
+    let precedence_tasks: Vec<(u64, &TaskReadIn)> = lines.iter().copied().zip(&task_table).collect();
+    if let Some(issue) = check_precedence(&precedence_tasks).into_iter().next(){
+        return Err(PpeError::BadRow {
+            file: file_path.to_owned(),
+            line: Some(issue.line),
+            column: Some(String::from(issue.column)),
+            message: issue.message,
+        });
     }
 
-    translate_task(task_table)
+    Ok(translate_task(task_table))
+}
 
+/// A task whose id or predecessors can't be used.
+pub(crate) struct PrecedenceIssue{
+    pub(crate) line: u64,
+    pub(crate) column: &'static str,
+    pub(crate) message: String,
+}
+
+/// Checks task ids and predecessors with the line of each task: ids are unique within a version,
+/// predecessors are tasks of the same version and no task waits for itself, directly or through a cycle.
+pub(crate) fn check_precedence(tasks: &[(u64, &TaskReadIn)]) -> Vec<PrecedenceIssue>{
+    let mut issues = vec![];
+    let mut ids: HashMap<(&str, &str), usize> = HashMap::new();
+    for (i, (line, task)) in tasks.iter().enumerate(){
+        let id = match task.id.as_deref().map(str::trim).filter(|id| !id.is_empty()){
+            Some(id) => id,
+            None => continue,
+        };
+        match ids.get(&(task.version.as_str(), id)){
+            Some(first) => issues.push(PrecedenceIssue { line: *line, column: "id",
+                message: format!("Task id {} of version {} is already used in line {}", id, task.version, tasks[*first].0) }),
+            None => { ids.insert((task.version.as_str(), id), i); }
+        }
+    }
+
+    // Successors of every task, to peel off the tasks that can start one after another
+    let mut successors: Vec<Vec<usize>> = vec![vec![]; tasks.len()];
+    let mut waiting: Vec<usize> = vec![0; tasks.len()];
+    let mut self_dependent = vec![false; tasks.len()];
+    for (i, (line, task)) in tasks.iter().enumerate(){
+        for predecessor in split_names(&task.predecessors){
+            match ids.get(&(task.version.as_str(), predecessor.as_str())){
+                Some(&position) => {
+                    if position == i{
+                        self_dependent[i] = true;
+                        issues.push(PrecedenceIssue { line: *line, column: "predecessors", message: format!("Task {} is its own predecessor", predecessor) });
+                    }
+                    successors[position].push(i);
+                    waiting[i] += 1;
+                }
+                None => issues.push(PrecedenceIssue { line: *line, column: "predecessors",
+                    message: format!("Predecessor {} is not a task of version {}", predecessor, task.version) }),
+            }
+        }
+    }
+    let mut ready: VecDeque<usize> = (0..tasks.len()).filter(|i| waiting[*i] == 0).collect();
+    while let Some(i) = ready.pop_front(){
+        for &successor in &successors[i]{
+            waiting[successor] -= 1;
+            if waiting[successor] == 0{
+                ready.push_back(successor);
+            }
+        }
+    }
+    for (i, (line, task)) in tasks.iter().enumerate(){
+        if waiting[i] > 0 && !self_dependent[i]{
+            issues.push(PrecedenceIssue { line: *line, column: "predecessors",
+                message: format!("Task of SOT {} waits for a cycle of predecessors in version {} and will never be worked on", task.sot, task.version) });
+        }
+    }
+    issues
 }
 
 fn translate_task(tasks: Vec<TaskReadIn>) -> Vec<Task>{
     let mut task_list = vec![];
-    // TODO Need to add support for multiple stations in tasks
+    // Tasks spanning multiple stations list them separated by ";" in line order, e.g. S1;S2
     for task in tasks{
-        task_list.push(Task::new(task.workload,
-            vec![task.station],
+        let stations: Vec<String> = task.station.split(";").map(|s| s.trim().to_string()).collect();
+        let mut new_task = Task::new(task.workload,
+            stations,
             task.sot, 
             task.version,
-        ));
+        );
+        new_task.set_qualifications(split_names(&task.qualifications));
+        new_task.set_predicted_workload(task.predicted_workload);
+        new_task.set_id(task.id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()));
+        new_task.set_predecessors(split_names(&task.predecessors));
+        task_list.push(new_task);
     }
     task_list 
 }
 
-pub fn write_to_output(osw_data: Vec<f64>){
-    let mut rdr = csv::Reader::from_path("data/tactplan.csv").expect("Failed to open file");
-    let mut wtr = csv::Writer::from_path("data/output.csv").expect("Failed to open file");
-    
-    let mut headers = rdr.headers().expect("Failed to get headers in tactplan.").clone();
+pub fn write_to_output(hangar: &Hangar, source: &DataSource, sink: &mut OutputSink) -> Result<(), PpeError>{
+    let osw_data = hangar.get_osw();
+    let msn_dates = hangar.get_msn_dates();
+    let learning_factors = hangar.get_learning_factors().to_owned();
+    let hangar = hangar.get_name();
+    let input_path = &source.describe(InputFile::TactPlan);
+    let output_path = &format!("output_{}.csv", hangar);
+    let (mut rdr, mut headers) = open_reader(source, InputFile::TactPlan)?;
+    let hangar_column = headers.iter().position(|h| h == "hangar");
+    let mut wtr = csv::Writer::from_writer(vec![]);
 
     headers.push_field("osw");
-    wtr.write_record(&headers).expect("Failed to write headers");
+    headers.push_field("entry_date");
+    headers.push_field("exit_date");
+    headers.push_field("learning_factor");
+    wtr.write_record(&headers).map_err(|e| PpeError::output(output_path, e))?;
 
 
-    for (i, result) in rdr.records().enumerate(){
-        let mut record = result.expect("Failed to read record");
+    let mut i = 0;
+    for result in rdr.records(){
+        let mut record = result.map_err(|e| PpeError::bad_row(input_path, e, Some(&headers)))?;
+        let row_hangar = hangar_column.and_then(|c| record.get(c)).map(|h| h.to_string());
+        if !in_hangar(&row_hangar, hangar){
+            continue;
+        }
 
         if let Some(new_value) = osw_data.get(i){
             record.push_field(new_value.to_string().as_str());
@@ -64,133 +254,898 @@ pub fn write_to_output(osw_data: Vec<f64>){
         else{
             record.push_field("");
         }
+        // Dates are only known with a calendar start date
+        match msn_dates.get(i){
+            Some((entry, exit)) => {
+                record.push_field(&entry.to_string());
+                record.push_field(&exit.to_string());
+            }
+            None => {
+                record.push_field("");
+                record.push_field("");
+            }
+        }
+        match learning_factors.get(i){
+            Some(factor) => record.push_field(&factor.to_string()),
+            None => record.push_field(""),
+        }
+        i += 1;
 
-        wtr.write_record(&record).expect("Failed to write record");
+        wtr.write_record(&record).map_err(|e| PpeError::output(output_path, e))?;
     }
 
-    wtr.flush().expect("Failed to flush writer");
+    finish_writer(wtr, output_path, sink)
 }
 
-pub fn write_sot_util(sot_util: IndexMap<String, Vec<f64>>){
+pub fn write_sot_util(sot_util: IndexMap<String, Vec<f64>>, hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
     // ISSUEs: Not all SOT have the same number of tacts, need to make it only record on main tact, after all OSW has been finished.
     
     //Write a table, where each row is a tact and each column is an sot
-    let mut wtr = csv::Writer::from_path("data/sot_util.csv").expect("Failed to open file");
+    write_tact_table(sot_util, &format!("sot_util_{}.csv", hangar), sink)
+}
+
+/// Writes worker_hours_<hangar>.csv, each row is a tact and each column a worker.
+pub fn write_worker_hours(worker_hours: IndexMap<String, Vec<f64>>, hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    write_tact_table(worker_hours, &format!("worker_hours_{}.csv", hangar), sink)
+}
+
+/// Writes lending_<hangar>.csv, each row is a tact with the hours every SOT lent and borrowed.
+pub fn write_lending(lent_hours: IndexMap<String, Vec<f64>>, hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    write_tact_table(lent_hours, &format!("lending_{}.csv", hangar), sink)
+}
+
+/// Writes capacity_<hangar>.csv, the hours each capacity factor took away from every SOT over the run.
+pub fn write_capacity(waterfall: IndexMap<String, CapacityWaterfall>, hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    let output_path = &format!("capacity_{}.csv", hangar);
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(["sot", "scheduled_hours", "presence", "cvat", "hangar_efficiency", "shift_efficiency", "learning", "productive_hours"])
+        .map_err(|e| PpeError::output(output_path, e))?;
+
+    let mut total = CapacityWaterfall::default();
+    waterfall.values().for_each(|sot| total.add(sot));
+    for (name, waterfall) in waterfall.into_iter().chain([(String::from("total"), total)]){
+        wtr.write_record([
+            name,
+            waterfall.scheduled.to_string(),
+            waterfall.presence.to_string(),
+            waterfall.cvat.to_string(),
+            waterfall.hangar_efficiency.to_string(),
+            waterfall.shift_efficiency.to_string(),
+            waterfall.learning.to_string(),
+            waterfall.get_productive().to_string(),
+        ]).map_err(|e| PpeError::output(output_path, e))?;
+    }
+
+    finish_writer(wtr, output_path, sink)
+}
+
+/// Writes events_<hangar>.csv, every event of the run in the order it happened.
+pub fn write_events(events: &[Event], hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    let output_path = &format!("events_{}.csv", hangar);
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(["time", "event", "detail"]).map_err(|e| PpeError::output(output_path, e))?;
+    for event in events{
+        wtr.write_record([event.time.to_string(), event.kind.get_name().to_string(), event.kind.get_detail()])
+            .map_err(|e| PpeError::output(output_path, e))?;
+    }
+
+    finish_writer(wtr, output_path, sink)
+}
+
+/// Writes one row per MSN that left the hangar with its OSW, planned and actual line exit, delivery
+/// and the delay of the delivery against the planned exit, in line hours.
+pub fn write_deliveries(deliveries: &[Delivery], hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    let output_path = &format!("delivery_{}.csv", hangar);
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(["msn", "osw", "planned_exit", "exit", "delivery", "delay"]).map_err(|e| PpeError::output(output_path, e))?;
+    for delivery in deliveries{
+        wtr.write_record([
+            delivery.msn.to_string(),
+            delivery.osw.to_string(),
+            delivery.planned_exit.to_string(),
+            delivery.exit.to_string(),
+            delivery.delivery.to_string(),
+            delivery.get_delay().to_string(),
+        ]).map_err(|e| PpeError::output(output_path, e))?;
+    }
+
+    finish_writer(wtr, output_path, sink)
+}
+
+/// Writes one row per MSN, SOT and station with the predicted and actual workload and the OSW at
+/// line exit of the runs with either.
+pub fn write_comparison(rows: &[WorkloadComparison], hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    let output_path = &format!("comparison_{}.csv", hangar);
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(["msn", "sot", "station", "predicted_workload", "actual_workload", "workload_error", "predicted_osw", "actual_osw"])
+        .map_err(|e| PpeError::output(output_path, e))?;
+    for row in rows{
+        wtr.write_record([
+            row.msn.to_string(),
+            row.sot.clone(),
+            row.station.clone(),
+            row.predicted_workload.to_string(),
+            row.actual_workload.to_string(),
+            row.get_workload_error().to_string(),
+            row.predicted_osw.to_string(),
+            row.actual_osw.to_string(),
+        ]).map_err(|e| PpeError::output(output_path, e))?;
+    }
+
+    finish_writer(wtr, output_path, sink)
+}
+
+/// Writes one row per tact and one column per entry of the table.
+fn write_tact_table(table: IndexMap<String, Vec<f64>>, output_path: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    let mut wtr = csv::Writer::from_writer(vec![]);
 
     //Add Headers
     let mut headers: StringRecord = StringRecord::new();
-    let mut sot_names: Vec<String> = vec![];
     headers.push_field("tact");
-    sot_util.keys().for_each(|sot| {
-        headers.push_field(sot);
-        sot_names.push(sot.to_owned());
-    });
-    wtr.write_record(&headers).expect("Failed to write headers to sot_util.csv");
-
-    let last_sot = sot_names.len() -1;
-    let entry_length = sot_util[&sot_names[last_sot]].len();
-    //println!("All Entries of last SOT: {:?}", sot_util[&sot_names[last_sot]]);
+    table.keys().for_each(|column| headers.push_field(column));
+    wtr.write_record(&headers).map_err(|e| PpeError::output(output_path, e))?;
+
+    let entry_length = match table.values().last(){
+        Some(last_column) => last_column.len(),
+        None => 0,
+    };
     for row in 0..entry_length{
         let mut record: StringRecord = StringRecord::new();
         record.push_field(row.to_string().as_str());
-        for sot in &sot_names{
-            //println!("{}", sot_util[sot][row]);
-            record.push_field(sot_util[sot][row].to_string().as_str());
+        for values in table.values(){
+            record.push_field(values[row].to_string().as_str());
+        }
+        wtr.write_record(&record).map_err(|e| PpeError::output(output_path, e))?;
+    }
+
+    finish_writer(wtr, output_path, sink)
+}
+
+
+/// Writes one row per hangar with its exit OSW and utilization, followed by a total row.
+pub fn write_summary(hangars: &[Hangar], sink: &mut OutputSink) -> Result<(), PpeError>{
+    let output_path = "summary.csv";
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(["hangar", "msns", "total_osw", "max_osw", "mean_utilization", "overtime_hours", "overtime_cost"])
+        .map_err(|e| PpeError::output(output_path, e))?;
+
+    let mut all_osw: Vec<f64> = vec![];
+    for hangar in hangars{
+        let osw = hangar.get_osw();
+        all_osw.extend(osw);
+        wtr.write_record([
+            hangar.get_name().to_string(),
+            osw.len().to_string(),
+            osw.iter().sum::<f64>().to_string(),
+            osw.iter().cloned().fold(0.0, f64::max).to_string(),
+            hangar.get_mean_utilization().to_string(),
+            hangar.get_total_overtime().to_string(),
+            hangar.get_overtime_cost().to_string(),
+        ]).map_err(|e| PpeError::output(output_path, e))?;
+    }
+    let mean_utilization = if hangars.is_empty(){
+        0.0
+    } else {
+        hangars.iter().map(|h| h.get_mean_utilization()).sum::<f64>() / hangars.len() as f64
+    };
+    wtr.write_record([
+        String::from("total"),
+        all_osw.len().to_string(),
+        all_osw.iter().sum::<f64>().to_string(),
+        all_osw.iter().cloned().fold(0.0, f64::max).to_string(),
+        mean_utilization.to_string(),
+        hangars.iter().map(|h| h.get_total_overtime()).sum::<f64>().to_string(),
+        hangars.iter().map(|h| h.get_overtime_cost()).sum::<f64>().to_string(),
+    ]).map_err(|e| PpeError::output(output_path, e))?;
+
+    finish_writer(wtr, output_path, sink)
+}
+
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ExceptionReadIn{
+    pub(crate) date: NaiveDate,
+    #[serde(default)]
+    pub(crate) end_date: Option<NaiveDate>,
+    pub(crate) kind: String,
+    // Only used for reduced days
+    #[serde(default)]
+    pub(crate) capacity: Option<f64>,
+    #[serde(default)]
+    pub(crate) reason: Option<String>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+/// Reads exceptions.csv (date, kind, capacity, reason, optional end_date and hangar).
+/// The file is optional, without it there are no exception days.
+pub fn load_exceptions(source: &DataSource, hangar: &str) -> Result<Vec<CalendarException>, PpeError>{
+    let file_path = &source.describe(InputFile::Exceptions);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::Exceptions)?{
+        Some(reader) => reader,
+        None => return Ok(vec![]),
+    };
+
+    let mut exceptions = vec![];
+    for (i, line) in rdr.deserialize().enumerate(){
+        let res: ExceptionReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        let kind = ExceptionKind::parse(&res.kind).ok_or_else(|| PpeError::BadRow {
+            file: file_path.to_owned(),
+            line: Some(i as u64 + 2),
+            column: Some(String::from("kind")),
+            message: format!("Unknown kind {}, expected holiday, shutdown or reduced", res.kind),
+        })?;
+        let capacity = match kind{
+            ExceptionKind::Reduced => res.capacity.unwrap_or(0.5).clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+        let mut date = res.date;
+        while date <= res.end_date.unwrap_or(res.date){
+            exceptions.push(CalendarException { date, kind, capacity, reason: res.reason.clone().unwrap_or_default() });
+            date = date.succ_opt().expect("Date out of range");
+        }
+    }
+    Ok(exceptions)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WorkerReadIn{
+    pub(crate) id: String,
+    pub(crate) sot: String,
+    #[serde(default)]
+    pub(crate) efficiency: Option<f64>,
+    // Works the home SOT's shifts if empty
+    #[serde(default)]
+    pub(crate) shift: Option<String>,
+    // Separated by ";"
+    #[serde(default)]
+    pub(crate) qualifications: Option<String>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+/// Reads workers.csv (id, sot, optional efficiency, shift, qualifications and hangar).
+/// The file is optional, without it every SOT works with its bc headcount.
+pub fn load_workers(source: &DataSource, hangar: &str, shift_system: &ShiftSystem) -> Result<Vec<Worker>, PpeError>{
+    let file_path = &source.describe(InputFile::Workers);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::Workers)?{
+        Some(reader) => reader,
+        None => return Ok(vec![]),
+    };
+
+    let mut workers = vec![];
+    for line in rdr.deserialize(){
+        let res: WorkerReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        let mut worker = Worker::new(&res.id, &res.sot, res.efficiency.unwrap_or(1.0));
+        if let Some(shift) = split_names(&res.shift).first(){
+            let shift_share = shift_system.get_share(std::slice::from_ref(shift)).map_err(|shift| PpeError::UnknownShift {
+                shift,
+                context: format!("worker {} of hangar {} in workers.csv", res.id, hangar),
+            })?;
+            worker.set_shift(shift, shift_share);
+        }
+        worker.set_qualifications(split_names(&res.qualifications));
+        workers.push(worker);
+    }
+    Ok(workers)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SickLeaveReadIn{
+    // All SOTs if empty
+    #[serde(default)]
+    pub(crate) sot: Option<String>,
+    // 1 to 12, all months if empty
+    #[serde(default)]
+    pub(crate) month: Option<u32>,
+    pub(crate) rate: f64,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+/// Reads sickleave.csv (rate, optional sot, month and hangar). Returns None if there is neither
+/// the file nor a default rate, then no absences are sampled.
+pub fn load_sick_leave(source: &DataSource, hangar: &str, default_rate: Option<f64>, sots: &[SOT]) -> Result<Option<SickLeave>, PpeError>{
+    let file_path = &source.describe(InputFile::SickLeave);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::SickLeave)?{
+        Some(reader) => reader,
+        None => return Ok(default_rate.map(|rate| SickLeave::new(vec![], Some(rate)))),
+    };
+
+    let mut rates = vec![];
+    for line in rdr.deserialize(){
+        let res: SickLeaveReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        let sot = res.sot.filter(|sot| !sot.is_empty());
+        if let Some(sot) = &sot{
+            if !sots.iter().any(|s| &s.get_name() == sot){
+                return Err(PpeError::UnknownSot {
+                    sot: sot.to_owned(),
+                    context: format!("sick-leave rate of hangar {} in sickleave.csv", hangar),
+                });
+            }
+        }
+        rates.push(SickLeaveRate { sot, month: res.month, rate: res.rate });
+    }
+    Ok(Some(SickLeave::new(rates, default_rate)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LearningReadIn{
+    // All SOTs if empty
+    #[serde(default)]
+    pub(crate) sot: Option<String>,
+    // All versions if empty
+    #[serde(default)]
+    pub(crate) version: Option<String>,
+    pub(crate) rate: f64,
+    // The floor of the settings if empty
+    #[serde(default)]
+    pub(crate) floor: Option<f64>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+/// Reads learning.csv (rate, optional sot, version, floor and hangar). Returns None if there is
+/// neither the file nor a learning rate in the settings.
+pub fn load_learning(source: &DataSource, hangar: &str, settings: &LearningSettings, sots: &[SOT]) -> Result<Option<Learning>, PpeError>{
+    let file_path = &source.describe(InputFile::Learning);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::Learning)?{
+        Some(reader) => reader,
+        None => return Ok(settings.rate.map(|rate| Learning::new(settings.model, vec![], Some(rate), settings.floor))),
+    };
+
+    let mut curves = vec![];
+    for line in rdr.deserialize(){
+        let res: LearningReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        let sot = res.sot.filter(|sot| !sot.is_empty());
+        if let Some(sot) = &sot{
+            if !sots.iter().any(|s| &s.get_name() == sot){
+                return Err(PpeError::UnknownSot {
+                    sot: sot.to_owned(),
+                    context: format!("learning curve of hangar {} in learning.csv", hangar),
+                });
+            }
+        }
+        let version = res.version.filter(|version| !version.is_empty());
+        curves.push(LearningCurve { sot, version, rate: res.rate, floor: res.floor });
+    }
+    Ok(Some(Learning::new(settings.model, curves, settings.rate, settings.floor)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OvertimeReadIn{
+    pub(crate) sot: String,
+    pub(crate) max_per_tact: f64,
+    // No weekly limit if empty
+    #[serde(default)]
+    pub(crate) max_per_week: Option<f64>,
+    // Overtime is worked whenever workload is due if empty
+    #[serde(default)]
+    pub(crate) threshold: Option<f64>,
+    // The cost of the settings if empty
+    #[serde(default)]
+    pub(crate) cost: Option<f64>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+/// Reads overtime.csv (sot, max_per_tact, optional max_per_week, threshold, cost and hangar)
+/// and sets the overtime policy of the listed SOTs. Without the file no SOT works overtime.
+pub fn load_overtime(source: &DataSource, hangar: &str, default_cost: f64, sots: &mut [SOT]) -> Result<(), PpeError>{
+    let file_path = &source.describe(InputFile::Overtime);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::Overtime)?{
+        Some(reader) => reader,
+        None => return Ok(()),
+    };
+
+    for line in rdr.deserialize(){
+        let res: OvertimeReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
         }
-        wtr.write_record(&record).expect("Error writing SOT to sot_util.csv");
+        let sot = sots.iter_mut().find(|s| s.get_name() == res.sot).ok_or_else(|| PpeError::UnknownSot {
+            sot: res.sot.to_owned(),
+            context: format!("overtime policy of hangar {} in overtime.csv", hangar),
+        })?;
+        sot.set_overtime(OvertimePolicy {
+            max_per_tact: res.max_per_tact,
+            max_per_week: res.max_per_week,
+            threshold: res.threshold.unwrap_or(0.0),
+            cost_per_hour: res.cost.unwrap_or(default_cost),
+        });
     }
+    Ok(())
+}
 
-    wtr.flush().expect("Failed to flush writer for write_sot_util().");
+/// Writes overtime_<hangar>.csv, each row is a tact and each column the overtime worker hours of a SOT.
+pub fn write_overtime(overtime_hours: IndexMap<String, Vec<f64>>, hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    write_tact_table(overtime_hours, &format!("overtime_{}.csv", hangar), sink)
 }
 
+#[derive(Debug, Deserialize)]
+pub(crate) struct StaffingReadIn{
+    pub(crate) sot: String,
+    // One of from_tact and from_date, dates need a calendar
+    #[serde(default)]
+    pub(crate) from_tact: Option<usize>,
+    #[serde(default)]
+    pub(crate) from_date: Option<NaiveDate>,
+    pub(crate) bc: u16,
+    // cvat of sot.csv if empty
+    #[serde(default)]
+    pub(crate) cvat: Option<f64>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+/// Reads staffing.csv (sot, from_tact or from_date, bc, optional cvat and hangar) and gives every
+/// listed SOT its headcount changes. The file is optional. SOTs with workers in workers.csv can't
+/// be staffed by headcount.
+pub fn load_staffing(source: &DataSource, hangar: &str, calendar: Option<&Calendar>, tact_time: f64, sots: &mut [SOT]) -> Result<(), PpeError>{
+    let file_path = &source.describe(InputFile::Staffing);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::Staffing)?{
+        Some(reader) => reader,
+        None => return Ok(()),
+    };
+
+    let mut staffing: Vec<Vec<StaffingChange>> = vec![vec![]; sots.len()];
+    for (i, line) in rdr.deserialize().enumerate(){
+        let res: StaffingReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        let bad_row = |column: &str, message: String| PpeError::BadRow {
+            file: file_path.to_owned(),
+            line: Some(i as u64 + 2),
+            column: Some(String::from(column)),
+            message,
+        };
+        let position = sots.iter().position(|s| s.get_name() == res.sot).ok_or_else(|| PpeError::UnknownSot {
+            sot: res.sot.to_owned(),
+            context: format!("staffing plan of hangar {} in staffing.csv", hangar),
+        })?;
+        if sots[position].has_listed_workers(){
+            return Err(bad_row("sot", format!("SOT {} has workers in workers.csv, plan its staff there", res.sot)));
+        }
+        let tact = match (res.from_tact, res.from_date, calendar){
+            (Some(tact), _, _) => tact,
+            (None, Some(date), Some(calendar)) => calendar.get_tact_of_date(date, tact_time),
+            (None, Some(_), None) => return Err(bad_row("from_date", String::from("Dates need a calendar start date"))),
+            (None, None, _) => return Err(bad_row("from_tact", String::from("A staffing change needs from_tact or from_date"))),
+        };
+        staffing[position].push(StaffingChange { tact, bc: res.bc, cvat: res.cvat });
+    }
+    for (sot, changes) in sots.iter_mut().zip(staffing){
+        if !changes.is_empty(){
+            sot.set_staffing(changes);
+        }
+    }
+    Ok(())
+}
 
 #[derive(Debug, Deserialize)]
-struct MSNReadIn{
-    msn: u32,
-    version: String,
+pub(crate) struct DowntimeReadIn{
+    // The whole hangar if empty
+    #[serde(default)]
+    pub(crate) station: Option<String>,
+    // One of start_tact and start_date, dates need a calendar
+    #[serde(default)]
+    pub(crate) start_tact: Option<usize>,
+    #[serde(default)]
+    pub(crate) start_date: Option<NaiveDate>,
+    // Line hours
+    pub(crate) duration: f64,
+    #[serde(default)]
+    pub(crate) reason: String,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
 }
 
-pub fn get_tactplan() -> Vec<MSN>{
-    let task_table = read_tasks();
+/// Reads downtime.csv (optional station, start_tact or start_date, duration in line hours,
+/// reason and hangar). The file is optional. Rows without a reason are booked as "unknown".
+pub fn load_downtime(source: &DataSource, hangar: &str, calendar: Option<&Calendar>, tact_time: f64, station_names: &[String]) -> Result<Vec<Downtime>, PpeError>{
+    let file_path = &source.describe(InputFile::Downtime);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::Downtime)?{
+        Some(reader) => reader,
+        None => return Ok(vec![]),
+    };
 
-    let  file_path = "data/tactplan.csv";
-    let file = File::open(file_path).expect("Failed to open tactplan file");
+    let mut downtime = vec![];
+    for (i, line) in rdr.deserialize().enumerate(){
+        let res: DowntimeReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        let bad_row = |column: &str, message: &str| PpeError::BadRow {
+            file: file_path.to_owned(),
+            line: Some(i as u64 + 2),
+            column: Some(String::from(column)),
+            message: String::from(message),
+        };
+        let station = res.station.filter(|station| !station.is_empty());
+        if let Some(station) = &station{
+            if !station_names.contains(station){
+                return Err(PpeError::UnknownStation {
+                    station: station.to_owned(),
+                    context: format!("downtime of hangar {} in downtime.csv", hangar),
+                });
+            }
+        }
+        let start = match (res.start_tact, res.start_date, calendar){
+            (Some(tact), _, _) => tact as f64 * tact_time,
+            (None, Some(date), Some(calendar)) => calendar.get_hour_of_date(date),
+            (None, Some(_), None) => return Err(bad_row("start_date", "Dates need a calendar start date")),
+            (None, None, _) => return Err(bad_row("start_tact", "A downtime needs start_tact or start_date")),
+        };
+        // A downtime without end would stop a held MSN for good
+        if !res.duration.is_finite(){
+            return Err(bad_row("duration", "A downtime needs a finite number of hours"));
+        }
+        let reason = match res.reason.is_empty(){
+            true => String::from("unknown"),
+            false => res.reason,
+        };
+        // Station::new upper-cases names
+        let station = station.map(|station| station.to_ascii_uppercase());
+        downtime.push(Downtime { station, start, duration: res.duration, reason });
+    }
+    Ok(downtime)
+}
+
+/// Writes downtime_<hangar>.csv, each row is a tact and each column the downtime hours of a reason.
+pub fn write_downtime(downtime_hours: IndexMap<String, Vec<f64>>, hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    write_tact_table(downtime_hours, &format!("downtime_{}.csv", hangar), sink)
+}
 
-    let mut rdr = csv::Reader::from_reader(file);
+/// Writes blocked_<hangar>.csv, each row is a tact with the share of the available time every SOT
+/// waited for predecessors of its tasks.
+pub fn write_blocked(blocked_shares: IndexMap<String, Vec<f64>>, hangar: &str, sink: &mut OutputSink) -> Result<(), PpeError>{
+    write_tact_table(blocked_shares, &format!("blocked_{}.csv", hangar), sink)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct HolidayReadIn{
+    // A named worker, or a SOT with an optional headcount
+    #[serde(default)]
+    pub(crate) worker: Option<String>,
+    #[serde(default)]
+    pub(crate) sot: Option<String>,
+    pub(crate) start_date: NaiveDate,
+    #[serde(default)]
+    pub(crate) end_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub(crate) headcount: Option<u32>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+/// Reads holidays.csv (worker or sot, start_date, optional end_date, headcount and hangar).
+/// The file is optional, holidays need a calendar. Workers and SOTs must be known to the hangar.
+pub fn load_holidays(source: &DataSource, hangar: &str, calendar: Option<&Calendar>, sots: &[SOT]) -> Result<HolidayPlan, PpeError>{
+    let file_path = &source.describe(InputFile::Holidays);
+    let (mut rdr, headers) = match open_optional_reader(source, InputFile::Holidays)?{
+        Some(reader) => reader,
+        None => return Ok(HolidayPlan::default()),
+    };
+
+    let mut holidays = vec![];
+    for (i, line) in rdr.deserialize().enumerate(){
+        let res: HolidayReadIn = line.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        if calendar.is_none(){
+            return Err(PpeError::BadRow {
+                file: file_path.to_owned(),
+                line: Some(i as u64 + 2),
+                column: Some(String::from("start_date")),
+                message: String::from("Dates need a calendar start date"),
+            });
+        }
+        let worker = res.worker.filter(|worker| !worker.is_empty());
+        let sot = res.sot.filter(|sot| !sot.is_empty());
+        let context = format!("holiday in line {} of hangar {} in holidays.csv", i + 2, hangar);
+        match (&worker, &sot){
+            (Some(worker), _) => {
+                if !sots.iter().any(|s| s.knows_worker(worker)){
+                    return Err(PpeError::UnknownWorker { worker: worker.to_owned(), context });
+                }
+            }
+            (None, Some(sot)) => {
+                if !sots.iter().any(|s| &s.get_name() == sot){
+                    return Err(PpeError::UnknownSot { sot: sot.to_owned(), context });
+                }
+            }
+            (None, None) => return Err(PpeError::BadRow {
+                file: file_path.to_owned(),
+                line: Some(i as u64 + 2),
+                column: None,
+                message: String::from("A holiday needs a worker or a sot"),
+            }),
+        }
+        holidays.push(Holiday {
+            worker,
+            sot,
+            start: res.start_date,
+            end: res.end_date.unwrap_or(res.start_date),
+            headcount: res.headcount,
+        });
+    }
+    Ok(HolidayPlan::new(holidays))
+}
+
+/// Gives every SOT its workers from workers.csv. SOTs without listed workers keep their bc headcount.
+pub fn assign_workers(sots: &mut [SOT], workers: Vec<Worker>, hangar: &str) -> Result<(), PpeError>{
+    let mut by_sot: Vec<Vec<Worker>> = vec![vec![]; sots.len()];
+    for worker in workers{
+        match sots.iter().position(|sot| sot.get_name() == worker.get_sot()){
+            Some(i) => by_sot[i].push(worker),
+            None => return Err(PpeError::UnknownSot {
+                sot: worker.get_sot().to_owned(),
+                context: format!("worker {} of hangar {} in workers.csv", worker.get_id(), hangar),
+            }),
+        }
+    }
+    for (sot, workers) in sots.iter_mut().zip(by_sot){
+        if !workers.is_empty(){
+            sot.set_workers(workers);
+        }
+    }
+    Ok(())
+}
+
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MSNReadIn{
+    pub(crate) msn: u32,
+    pub(crate) version: String,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
+
+pub fn get_tactplan(source: &DataSource, hangar: &str) -> Result<Vec<MSN>, PpeError>{
+    // Group the tasks by version once, instead of filtering the whole table for every MSN
+    let mut versions = Interner::new();
+    let mut tasks_by_version: Vec<Vec<Task>> = vec![];
+    for task in read_tasks(source, hangar)?{
+        let version_id = versions.intern(task.get_version());
+        if version_id == tasks_by_version.len(){
+            tasks_by_version.push(vec![]);
+        }
+        tasks_by_version[version_id].push(task);
+    }
+    let no_tasks: Vec<Task> = vec![];
+    // MSNs of a version only differ in their number, so each version is built once and copied
+    let mut prototypes: Vec<Option<MSN>> = vec![None; tasks_by_version.len()];
+
+    let file_path = &source.describe(InputFile::TactPlan);
+    let (mut rdr, headers) = open_reader(source, InputFile::TactPlan)?;
 
     let mut tact_plan = vec![];
     for msn in rdr.deserialize(){
-        let res: MSNReadIn = msn.expect("Failed to read Data from tactplan.csv. Confirm the file has columns: msn, version\nand that all data is in the correct column.\n");
-        let msn: MSN = MSN::new(res.msn, &res.version, &task_table);
+        let res: MSNReadIn = msn.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
+        let msn = match versions.get(&res.version){
+            Some(version_id) => prototypes[version_id].get_or_insert_with(|| MSN::new(res.msn, &res.version, &tasks_by_version[version_id])).with_number(res.msn),
+            None => MSN::new(res.msn, &res.version, &no_tasks),
+        };
         tact_plan.push(msn);
     }
 
-    tact_plan
+    Ok(tact_plan)
 
 }
 
-pub fn get_stations() -> Vec<Station>{
-    let mut station_strings  = vec![];
-    let mut stations: Vec<Station> = vec![];
+/// Checks that every row of tactplan.csv names its hangar. A row without one is planned in every
+/// hangar, so it is only accepted when a single hangar is simulated.
+pub fn check_tactplan_hangars(source: &DataSource) -> Result<(), PpeError>{
+    let file_path = &source.describe(InputFile::TactPlan);
+    let (mut rdr, headers) = open_reader(source, InputFile::TactPlan)?;
+    for (i, msn) in rdr.deserialize().enumerate(){
+        let res: MSNReadIn = msn.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if res.hangar.as_deref().unwrap_or("").is_empty(){
+            return Err(PpeError::BadRow {
+                file: file_path.to_owned(),
+                line: Some(i as u64 + 2),
+                column: Some(String::from("hangar")),
+                message: format!("MSN {} has no hangar, but more than one hangar is simulated", res.msn),
+            });
+        }
+    }
+    Ok(())
+}
 
-    let file_path = "data/stations.csv";
-    let file = File::open(file_path).expect("Failed to open file");
+pub fn get_stations(source: &DataSource, hangar: &str) -> Result<Vec<Station>, PpeError>{
+    let stations = get_station_names(source, hangar)?
+        .iter()
+        .map(|name| Station::new(name))
+        .collect();
+    Ok(stations)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct StationReadIn{
+    pub(crate) station: String,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+}
 
-    let mut rdr = csv::Reader::from_reader(file);
+fn read_stations(source: &DataSource) -> Result<Vec<StationReadIn>, PpeError>{
+    let file_path = &source.describe(InputFile::Stations);
+    let (mut rdr, headers) = open_reader(source, InputFile::Stations)?;
 
+    let mut stations = vec![];
     for station in rdr.deserialize(){
-        let res: String = station.expect("Failed to read Stations");
-        if !station_strings.contains(&res){
-            station_strings.push(res);
-            stations.push(Station::new(&station_strings[station_strings.len()-1]));
-        }
+        let res: StationReadIn = station.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        stations.push(res);
     }
-    stations
+    Ok(stations)
 }
 
-pub fn get_station_names() -> Vec<String>{
+pub fn get_station_names(source: &DataSource, hangar: &str) -> Result<Vec<String>, PpeError>{
     let mut station_strings  = vec![];
 
-    let file_path = "data/stations.csv";
-    let file = File::open(file_path).expect("Failed to open file");
+    for res in read_stations(source)?{
+        if in_hangar(&res.hangar, hangar) && !station_strings.contains(&res.station){
+            station_strings.push(res.station);
+        }
+    }
+    Ok(station_strings)
+}
 
-    let mut rdr = csv::Reader::from_reader(file);
+/// All hangars named in stations.csv, in order of appearance. Empty if the file has no hangar column.
+pub fn get_hangar_names(source: &DataSource) -> Result<Vec<String>, PpeError>{
+    let mut hangar_names: Vec<String> = vec![];
 
-    for station in rdr.deserialize(){
-        let res: String = station.expect("Failed to read Stations");
-        if !station_strings.contains(&res){
-            station_strings.push(res);
+    for res in read_stations(source)?{
+        if let Some(hangar) = res.hangar{
+            if !hangar.is_empty() && !hangar_names.contains(&hangar){
+                hangar_names.push(hangar);
+            }
         }
     }
-    station_strings
+    Ok(hangar_names)
 }
 
 #[derive(Debug, Deserialize)]
-struct SOTReadIn{
-    sot: String,
-    stations: String,
-    bc: u16,
-    cvat: f64,
+pub(crate) struct SOTReadIn{
+    pub(crate) sot: String,
+    pub(crate) stations: String,
+    pub(crate) bc: u16,
+    // The capacity factors of the settings if empty
+    #[serde(default)]
+    pub(crate) cvat: Option<f64>,
+    #[serde(default)]
+    pub(crate) absence: Option<f64>,
+    #[serde(default)]
+    pub(crate) technical_efficiency: Option<f64>,
+    #[serde(default)]
+    pub(crate) shift_efficiency: Option<f64>,
+    #[serde(default)]
+    pub(crate) hangar: Option<String>,
+    // Shift names separated by ";", all shifts if empty
+    #[serde(default)]
+    pub(crate) shifts: Option<String>,
+    // Dispatch rule, the one of the settings if empty
+    #[serde(default)]
+    pub(crate) dispatch: Option<String>,
 }
 
-pub fn load_sots(sot_inefficiency: f64) -> Vec<SOT>{
+/// Splits a ";" separated list of shifts or qualifications, dropping empty entries.
+pub(crate) fn split_names(names: &Option<String>) -> Vec<String>{
+    match names{
+        Some(names) => names.split(";").map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+        None => vec![],
+    }
+}
+
+/// Reads sot.csv (sot, bc, stations, optional cvat, absence, technical_efficiency, shift_efficiency,
+/// shifts, dispatch and hangar). Capacity factors and dispatch rules a SOT doesn't list are taken
+/// from the given defaults.
+pub fn load_sots(source: &DataSource, hangar: &str, defaults: &CapacityFactors, dispatch: Dispatch, shift_system: &ShiftSystem) -> Result<Vec<SOT>, PpeError>{
     let mut sots: Vec<SOT> = vec![];
-    let file_path = "data/sot.csv";
-    let file = File::open(file_path).expect("Failed to open file");
+    let station_names = get_station_names(source, hangar)?;
+    let file_path = &source.describe(InputFile::Sot);
+    let (mut rdr, headers) = open_reader(source, InputFile::Sot)?;
 
-    let mut rdr= csv::Reader::from_reader(file);
-    for sot in rdr.deserialize(){
-        let res: SOTReadIn = sot.expect("Failed to read SOT. Please check sot.csv for potential mismatches between header and data.\nAlso confirm the headers are all correctly spelled: sot, bc, cvat, stations\n");
+    for (i, sot) in rdr.deserialize().enumerate(){
+        let res: SOTReadIn = sot.map_err(|e| PpeError::bad_row(file_path, e, Some(&headers)))?;
+        if !in_hangar(&res.hangar, hangar){
+            continue;
+        }
         let stations: Vec<String> = res.stations.split(";").map(|s| s.to_string()).collect();
         for station in &stations{
-            if !get_station_names().contains(station){
-                panic!("Station {} not in station.csv list. Please also check sot {} in sot.csv", station, res.sot);
+            if !station_names.contains(station){
+                return Err(PpeError::UnknownStation {
+                    station: station.to_owned(),
+                    context: format!("sot {} of hangar {} in sot.csv", res.sot, hangar),
+                });
             }
         }
-        sots.push(SOT::new(&res.sot, stations, res.bc, res.cvat-sot_inefficiency));
+        let shifts = split_names(&res.shifts);
+        let shift_share = shift_system.get_share(&shifts).map_err(|shift| PpeError::UnknownShift {
+            shift,
+            context: format!("sot {} of hangar {} in sot.csv", res.sot, hangar),
+        })?;
+        let mut sot = SOT::new(&res.sot, stations, res.bc, res.cvat.unwrap_or(defaults.cvat));
+        sot.set_capacity(CapacityFactors {
+            presence: res.absence.map(|absence| 1.0 - absence).unwrap_or(defaults.presence),
+            cvat: res.cvat.unwrap_or(defaults.cvat),
+            hangar_efficiency: res.technical_efficiency.unwrap_or(defaults.hangar_efficiency),
+            shift_efficiency: res.shift_efficiency.unwrap_or(defaults.shift_efficiency),
+        });
+        sot.set_shifts(shifts, shift_share);
+        let rule = match res.dispatch.as_deref().filter(|rule| !rule.trim().is_empty()){
+            Some(rule) => Dispatch::parse(rule).ok_or_else(|| PpeError::BadRow {
+                file: file_path.to_owned(),
+                line: Some(i as u64 + 2),
+                column: Some(String::from("dispatch")),
+                message: format!("Unknown dispatch rule {}", rule),
+            })?,
+            None => dispatch,
+        };
+        sot.set_dispatch_rule(rule.get_rule());
+        sots.push(sot);
     }
 
-    sots
+    Ok(sots)
+}
+
+/// Checks that the stations of a task spanning several stations follow each other in the line
+/// order of stations.csv. Returns what is wrong, unknown stations are left to the caller.
+pub(crate) fn check_station_order(stations: &[String], station_names: &[String]) -> Option<String>{
+    let positions: Vec<usize> = stations.iter().filter_map(|station| station_names.iter().position(|name| name == station)).collect();
+    match positions.windows(2).all(|pair| pair[1] == pair[0] + 1){
+        true => None,
+        false => Some(format!("Stations {} are not consecutive in the line order of stations.csv", stations.join(";"))),
+    }
+}
+
+/// Checks that every task of the tact plan references a known station and SOT, and that tasks
+/// spanning several stations list them in line order without gaps.
+pub fn check_task_references(source: &DataSource, tact_plan: &[MSN], hangar: &str, station_names: &[String], sots: &[SOT]) -> Result<(), PpeError>{
+    let sot_names: Vec<String> = sots.iter().map(|sot| sot.get_name()).collect();
+    for msn in tact_plan{
+        for task in msn.get_all_tasks(){
+            for station in task.get_stations(){
+                if !station_names.contains(&station){
+                    return Err(PpeError::UnknownStation {
+                        station,
+                        context: format!("task of version {} of hangar {} in tasks.csv", task.get_version(), hangar),
+                    });
+                }
+            }
+            if let Some(message) = check_station_order(task.get_station_refs(), station_names){
+                return Err(PpeError::BadRow {
+                    file: source.describe(InputFile::Tasks),
+                    line: None,
+                    column: Some(String::from("station")),
+                    message: format!("{} in a task of version {} of hangar {}", message, task.get_version(), hangar),
+                });
+            }
+            if !sot_names.contains(task.get_sot()){
+                return Err(PpeError::UnknownSot {
+                    sot: task.get_sot().to_owned(),
+                    context: format!("task of version {} of hangar {} in tasks.csv", task.get_version(), hangar),
+                });
+            }
+        }
+    }
+    Ok(())
 }
 
 /*
@@ -212,10 +1167,11 @@ pub fn write_osw_to_output(osw: Vec<f64>) -> Result<(), csv::Error>{
 #[cfg(test)]
 mod tests{
     use super::*;
+    use crate::datasource::fixture;
 
     #[test]
     fn tasks_not_empty(){
-        let tasks = read_tasks();
+        let tasks = read_tasks(&fixture(), "1").unwrap();
 
         assert!(tasks[0].get_stations() != [""]);
         assert!(tasks[0].get_workload() != 0.);
@@ -223,24 +1179,156 @@ mod tests{
         //assert!(tasks[0].get_cvat() == 0.625);
     }
 
+    #[test]
+    fn multi_station_tasks_follow_the_line(){
+        let check = |stations: &str| {
+            let source = fixture().with_data(InputFile::Tasks, &format!("station,sot,version,workload\n{},MECH,A,10\n", stations));
+            let sots = load_sots(&source, "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()).unwrap();
+            check_task_references(&source, &get_tactplan(&source, "1").unwrap(), "1", &get_station_names(&source, "1").unwrap(), &sots)
+        };
+        assert!(check("S1;S2;S3").is_ok());
+        assert!(matches!(check("S2;S1"), Err(PpeError::BadRow { .. })));
+        assert!(matches!(check("S1;S3"), Err(PpeError::BadRow { .. })));
+    }
+
+    #[test]
+    fn unusable_precedence_is_rejected(){
+        let column = |tasks: &str| match read_tasks(&DataSource::memory().with_data(InputFile::Tasks, tasks), "1"){
+            Err(PpeError::BadRow { line, column, .. }) => (line, column),
+            other => panic!("expected a bad row, got {:?}", other.err()),
+        };
+        let header = "station,sot,version,workload,id,predecessors\n";
+        assert_eq!(column(&format!("{}S1,MECH,A,1,T1,T9\n", header)), (Some(2), Some(String::from("predecessors"))));
+        assert_eq!(column(&format!("{}S1,MECH,A,1,T1,\nS1,MECH,A,1,T1,\n", header)), (Some(3), Some(String::from("id"))));
+        assert_eq!(column(&format!("{}S1,MECH,A,1,T1,T2\nS1,MECH,A,1,T2,T1\n", header)), (Some(2), Some(String::from("predecessors"))));
+        // Ids only have to be unique within a version
+        assert!(read_tasks(&DataSource::memory().with_data(InputFile::Tasks, &format!("{}S1,MECH,A,1,T1,\nS1,MECH,B,1,T1,\n", header)), "1").is_ok());
+    }
+
+    #[test]
+    fn rows_are_filtered_by_hangar(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station,hangar\nS1,8\nS2,8\nS1,9\n");
+
+        assert_eq!(get_hangar_names(&source).unwrap(), vec!["8", "9"]);
+        assert_eq!(get_station_names(&source, "8").unwrap(), vec!["S1", "S2"]);
+        assert_eq!(get_station_names(&source, "9").unwrap(), vec!["S1"]);
+    }
+
+    #[test]
+    fn exception_ranges_are_expanded(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Exceptions, "date,end_date,kind,capacity,reason\n2026-08-03,2026-08-07,shutdown,,summer\n2026-12-24,,reduced,0.5,\n");
+        let exceptions = load_exceptions(&source, "1").unwrap();
+
+        assert_eq!(exceptions.len(), 6);
+        assert_eq!(exceptions[5].capacity, 0.5);
+        assert!(load_exceptions(&fixture(), "1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn unreadable_optional_input_is_an_error(){
+        let dir = std::env::temp_dir().join(format!("ppe-unreadable-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("exceptions.csv")).unwrap();
+        let exceptions = load_exceptions(&DataSource::directory(&dir), "1");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(exceptions.is_err());
+        assert!(load_exceptions(&DataSource::memory(), "1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn listed_workers_replace_headcount(){
+        let source = fixture().with_data(InputFile::Workers, "id,sot,efficiency,shift,qualifications\nW1,ELEC,0.9,late,NDT;WIRING\n");
+        let mut sots = load_sots(&source, "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()).unwrap();
+        let workers = load_workers(&source, "1", &ShiftSystem::default()).unwrap();
+        assert_eq!(workers[0].get_qualifications(), &vec![String::from("NDT"), String::from("WIRING")]);
+        assign_workers(&mut sots, workers, "1").unwrap();
+
+        assert_eq!(sots[0].get_workers().len(), 4);
+        assert_eq!(sots[1].get_workers().len(), 1);
+        assert_eq!(sots[1].get_workers()[0].get_shift_share(), Some(0.5));
+
+        let unknown = load_workers(&fixture().with_data(InputFile::Workers, "id,sot\nW1,PAINT\n"), "1", &ShiftSystem::default()).unwrap();
+        assert!(matches!(assign_workers(&mut sots, unknown, "1"), Err(PpeError::UnknownSot { .. })));
+    }
+
+    #[test]
+    fn sick_leave_only_with_file_or_rate(){
+        let sots = load_sots(&fixture(), "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()).unwrap();
+        assert!(load_sick_leave(&fixture(), "1", None, &sots).unwrap().is_none());
+        assert_eq!(load_sick_leave(&fixture(), "1", Some(0.1), &sots).unwrap().unwrap().get_rate("MECH", None), 0.1);
+
+        let source = fixture().with_data(InputFile::SickLeave, "sot,month,rate\nMECH,,0.2\n,12,0.3\n");
+        let sick_leave = load_sick_leave(&source, "1", None, &sots).unwrap().unwrap();
+        assert_eq!(sick_leave.get_rate("MECH", Some(12)), 0.2);
+        assert_eq!(sick_leave.get_rate("ELEC", Some(12)), 0.3);
+        assert_eq!(sick_leave.get_rate("ELEC", Some(1)), 0.0);
+    }
+
+    #[test]
+    fn multi_station_tasks_are_split(){
+        let tasks = translate_task(vec![TaskReadIn{
+            station: String::from("S1; S2"),
+            sot: String::from("MECH"),
+            version: String::from("A"),
+            workload: 10.0,
+            predicted_workload: Some(12.0),
+            hangar: None,
+            qualifications: Some(String::from("NDT; ")),
+            id: None,
+            predecessors: None,
+        }]);
+
+        assert_eq!(tasks[0].get_stations(), vec!["S1", "S2"]);
+        assert_eq!(tasks[0].get_qualifications(), &vec![String::from("NDT")]);
+        assert_eq!(tasks[0].get_predicted_workload(), Some(12.0));
+    }
+
     #[test]
     fn tact_plan_not_empty(){
-        let tact_plan = get_tactplan();
+        let tact_plan = get_tactplan(&fixture(), "1").unwrap();
 
         assert!(tact_plan[0].get_workload() > 0.0);
     }
 
     #[test]
     fn stations_not_empty(){
-        let stations = get_stations();
+        let stations = get_stations(&fixture(), "1").unwrap();
 
         assert!(stations[0].get_name() != "");
     }
 
     #[test]
     fn sot_not_empty(){
-        let sots = load_sots(0.086);
+        let sots = load_sots(&fixture(), "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()).unwrap();
 
         assert!(sots[0].get_name() != "");
     }
+
+    #[test]
+    fn sot_capacity_factors_override_defaults(){
+        let source = fixture().with_data(InputFile::Sot, "sot,bc,cvat,stations,absence,shift_efficiency
+MECH,4,0.8,S1;S2,0.1,
+ELEC,2,,S3;S4,,0.9
+");
+        let defaults = CapacityFactors { presence: 0.95, cvat: 0.7, hangar_efficiency: 0.98, shift_efficiency: 1.0 };
+        let sots = load_sots(&source, "1", &defaults, Dispatch::default(), &ShiftSystem::default()).unwrap();
+
+        assert_eq!(sots[0].get_capacity(), &CapacityFactors { presence: 0.9, cvat: 0.8, ..defaults });
+        assert_eq!(sots[1].get_capacity(), &CapacityFactors { shift_efficiency: 0.9, ..defaults });
+    }
+
+    #[test]
+    fn sot_dispatch_rule_overrides_default(){
+        let source = fixture().with_data(InputFile::Sot, "sot,bc,cvat,stations,dispatch\nMECH,4,0.8,S1;S2,shortest-task\nELEC,2,0.9,S3;S4,\n");
+        let sots = load_sots(&source, "1", &CapacityFactors::new(1.0), Dispatch::CriticalPath, &ShiftSystem::default()).unwrap();
+        assert_eq!(sots[0].get_dispatch_rule().get_name(), "shortest_task");
+        assert_eq!(sots[1].get_dispatch_rule().get_name(), "critical_path");
+
+        let source = fixture().with_data(InputFile::Sot, "sot,bc,cvat,stations,dispatch\nMECH,4,0.8,S1;S2,\nELEC,2,0.9,S3;S4,random\n");
+        match load_sots(&source, "1", &CapacityFactors::new(1.0), Dispatch::default(), &ShiftSystem::default()){
+            Err(PpeError::BadRow { line, column, .. }) => assert_eq!((line, column.as_deref()), (Some(3), Some("dispatch"))),
+            other => panic!("Expected a bad row, got {:?}", other.map(|sots| sots.len())),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,677 @@
+use std::fmt;
+use crate::datasource::{DataSource, InputFile};
+use crate::error::PpeError;
+use crate::settings::Settings;
+use crate::util::{self, in_hangar, split_names, read_table, ExceptionReadIn, MSNReadIn, SOTReadIn, HolidayReadIn, LearningReadIn, OvertimeReadIn, SickLeaveReadIn, StaffingReadIn, DowntimeReadIn, StationReadIn, TaskReadIn, WorkerReadIn};
+use crate::calendar::ExceptionKind;
+use crate::dispatch::Dispatch;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity{
+    // The simulation can't run or would give wrong results
+    Error,
+    // The data is suspicious but can be simulated
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue{
+    pub severity: Severity,
+    pub file: String,
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        let severity = match self.severity{
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.line{
+            Some(line) => write!(f, "{}: {}:{}: {}", severity, self.file, line, self.message),
+            None => write!(f, "{}: {}: {}", severity, self.file, self.message),
+        }
+    }
+}
+
+/// All problems found in the input data, instead of stopping at the first one.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport{
+    issues: Vec<Issue>,
+}
+
+impl ValidationReport{
+    pub fn new() -> Self{
+        Self { issues: vec![] }
+    }
+
+    fn push(&mut self, severity: Severity, file: &str, line: Option<u64>, message: String){
+        self.issues.push(Issue { severity, file: String::from(file), line, message });
+    }
+
+    fn push_error(&mut self, error: PpeError){
+        let (file, line) = match &error{
+            PpeError::MissingFile { file, .. } => (file.clone(), None),
+            PpeError::BadRow { file, line, .. } => (file.clone(), *line),
+            _ => (String::new(), None),
+        };
+        self.push(Severity::Error, &file, line, error.to_string());
+    }
+
+    pub fn get_issues(&self) -> &Vec<Issue>{
+        &self.issues
+    }
+
+    pub fn has_errors(&self) -> bool{
+        self.issues.iter().any(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn count(&self, severity: Severity) -> usize{
+        self.issues.iter().filter(|issue| issue.severity == severity).count()
+    }
+}
+
+impl fmt::Display for ValidationReport{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        for issue in &self.issues{
+            writeln!(f, "{}", issue)?;
+        }
+        write!(f, "{} error(s), {} warning(s)", self.count(Severity::Error), self.count(Severity::Warning))
+    }
+}
+
+/// Reads one file, reporting unreadable files and rows instead of failing.
+fn read_rows<T: serde::de::DeserializeOwned>(source: &DataSource, file: InputFile, report: &mut ValidationReport) -> Vec<(u64, T)>{
+    match read_table::<T>(source, file){
+        Ok(rows) => rows.into_iter().filter_map(|row| match row.value{
+            Ok(value) => Some((row.line, value)),
+            Err(e) => {
+                report.push_error(e);
+                None
+            }
+        }).collect(),
+        Err(e) => {
+            report.push_error(e);
+            vec![]
+        }
+    }
+}
+
+/// Reads an input that may be left out, see DataSource::open_optional.
+fn read_optional_rows<T: serde::de::DeserializeOwned>(source: &DataSource, file: InputFile, report: &mut ValidationReport) -> Vec<(u64, T)>{
+    match source.open_optional(file){
+        Ok(Some(_)) => read_rows(source, file, report),
+        Ok(None) => vec![],
+        Err(e) => {
+            report.push_error(e);
+            vec![]
+        }
+    }
+}
+
+fn split_list(list: &str) -> Vec<String>{
+    list.split(";").map(|s| s.trim().to_string()).collect()
+}
+
+/// Checks a station referenced in a task or SOT. Station::new upper-cases names, so references
+/// have to be upper case to be found in the hangar.
+fn check_station_reference(station: &str, known: &[String], hangar: &str, file: &str, line: u64, report: &mut ValidationReport){
+    let upper = station.to_ascii_uppercase();
+    if known.iter().any(|s| s.to_ascii_uppercase() == upper){
+        if station != upper{
+            report.push(Severity::Error, file, Some(line),
+                format!("Station '{}' does not match the upper-cased hangar station '{}'", station, upper));
+        }
+    }
+    else{
+        report.push(Severity::Error, file, Some(line),
+            format!("Station {} is not listed for hangar {} in stations.csv", station, hangar));
+    }
+}
+
+/// Checks a SOT referenced by another file against the SOTs of the hangar in sot.csv.
+/// Returns whether the SOT is known.
+fn check_sot_reference(sot: &str, known: &[String], hangar: &str, file: &str, line: u64, report: &mut ValidationReport) -> bool{
+    if known.iter().any(|s| s == sot){
+        return true;
+    }
+    let message = match known.iter().find(|s| s.eq_ignore_ascii_case(sot)){
+        Some(similar) => format!("SOT '{}' differs in case from SOT '{}' in sot.csv", sot, similar),
+        None => format!("SOT {} is not listed for hangar {} in sot.csv", sot, hangar),
+    };
+    report.push(Severity::Error, file, Some(line), message);
+    false
+}
+
+/// Names of the stations, SOTs and workers of a hangar, which the other files reference.
+struct HangarRefs<'a>{
+    name: &'a str,
+    stations: Vec<String>,
+    sots: Vec<String>,
+    // Workers of workers.csv, or the anonymous <SOT>-<n> of SOTs without listed workers,
+    // including those the staffing plan hires
+    workers: Vec<String>,
+}
+
+impl<'a> HangarRefs<'a>{
+    fn new(name: &'a str, stations: &[(u64, StationReadIn)], sots: &[(u64, SOTReadIn)], workers: &[(u64, WorkerReadIn)], staffing: &[(u64, StaffingReadIn)]) -> Self{
+        let hangar_sots: Vec<&SOTReadIn> = sots.iter().filter(|(_, s)| in_hangar(&s.hangar, name)).map(|(_, s)| s).collect();
+        let hangar_workers: Vec<&WorkerReadIn> = workers.iter().filter(|(_, w)| in_hangar(&w.hangar, name)).map(|(_, w)| w).collect();
+        let mut worker_ids: Vec<String> = hangar_workers.iter().map(|w| w.id.clone()).collect();
+        for sot in hangar_sots.iter().filter(|sot| !hangar_workers.iter().any(|w| w.sot == sot.sot)){
+            let headcount = staffing.iter()
+                .filter(|(_, c)| in_hangar(&c.hangar, name) && c.sot == sot.sot)
+                .map(|(_, c)| c.bc)
+                .fold(sot.bc, u16::max);
+            worker_ids.extend((1..=headcount).map(|i| format!("{}-{}", sot.sot, i)));
+        }
+        Self {
+            name,
+            stations: stations.iter().filter(|(_, s)| in_hangar(&s.hangar, name)).map(|(_, s)| s.station.clone()).collect(),
+            sots: hangar_sots.iter().map(|s| s.sot.clone()).collect(),
+            workers: worker_ids,
+        }
+    }
+}
+
+/// Checks all input files of the given hangars against each other.
+/// Without hangars, all hangars named in stations.csv are checked.
+pub fn validate(source: &DataSource, hangars: &[String], settings: &Settings) -> ValidationReport{
+    let mut report = ValidationReport::new();
+    let stations: Vec<(u64, StationReadIn)> = read_rows(source, InputFile::Stations, &mut report);
+    let sots: Vec<(u64, SOTReadIn)> = read_rows(source, InputFile::Sot, &mut report);
+    let tasks: Vec<(u64, TaskReadIn)> = read_rows(source, InputFile::Tasks, &mut report);
+    let tact_plan: Vec<(u64, MSNReadIn)> = read_rows(source, InputFile::TactPlan, &mut report);
+    // exceptions.csv, workers.csv, sickleave.csv, holidays.csv, learning.csv, overtime.csv, staffing.csv and downtime.csv are optional
+    let exceptions: Vec<(u64, ExceptionReadIn)> = read_optional_rows(source, InputFile::Exceptions, &mut report);
+    let workers: Vec<(u64, WorkerReadIn)> = read_optional_rows(source, InputFile::Workers, &mut report);
+    let sick_leave: Vec<(u64, SickLeaveReadIn)> = read_optional_rows(source, InputFile::SickLeave, &mut report);
+    let holidays: Vec<(u64, HolidayReadIn)> = read_optional_rows(source, InputFile::Holidays, &mut report);
+    let learning: Vec<(u64, LearningReadIn)> = read_optional_rows(source, InputFile::Learning, &mut report);
+    let overtime: Vec<(u64, OvertimeReadIn)> = read_optional_rows(source, InputFile::Overtime, &mut report);
+    let staffing: Vec<(u64, StaffingReadIn)> = read_optional_rows(source, InputFile::Staffing, &mut report);
+    let downtime: Vec<(u64, DowntimeReadIn)> = read_optional_rows(source, InputFile::Downtime, &mut report);
+
+    let mut hangar_names = hangars.to_vec();
+    if hangar_names.is_empty(){
+        hangar_names = util::get_hangar_names(source).unwrap_or_default();
+    }
+    if hangar_names.is_empty(){
+        hangar_names.push(String::from(util::DEFAULT_HANGAR));
+    }
+    check_stations(source, &stations, &hangar_names, &mut report);
+    // Hangars without stations are reported above and not checked any further
+    let hangars: Vec<HangarRefs> = hangar_names.iter()
+        .map(|hangar| HangarRefs::new(hangar, &stations, &sots, &workers, &staffing))
+        .filter(|hangar| !hangar.stations.is_empty())
+        .collect();
+
+    check_settings(settings, &hangars, &mut report);
+    check_sot(source, &sots, &hangars, settings, &mut report);
+    check_tasks(source, &tasks, &workers, &hangars, &mut report);
+    check_tact_plan(source, &tact_plan, &tasks, hangar_names.len() > 1, &hangars, &mut report);
+    check_exceptions(source, &exceptions, &mut report);
+    check_workers(source, &workers, &hangars, settings, &mut report);
+    check_staffing(source, &staffing, &workers, &hangars, settings, &mut report);
+    check_holidays(source, &holidays, &hangars, settings, &mut report);
+    check_sick_leave(source, &sick_leave, &hangars, &mut report);
+    check_learning(source, &learning, &tact_plan, &hangars, &mut report);
+    check_overtime(source, &overtime, &hangars, &mut report);
+    check_downtime(source, &downtime, &hangars, settings, &mut report);
+    report
+}
+
+/// Unfinished work capacity and the technical efficiency of every hangar.
+fn check_settings(settings: &Settings, hangars: &[HangarRefs], report: &mut ValidationReport){
+    let unfinished = settings.get_unfinished_work();
+    if unfinished.has_area() && unfinished.capacity <= 0.0{
+        report.push(Severity::Error, "settings", None,
+            String::from("Unfinished work capacity of the rework area or delivery centre is not above 0"));
+    }
+    for hangar in hangars{
+        let efficiency = settings.get_hangar_efficiency(hangar.name);
+        if !(efficiency > 0.0 && efficiency <= 1.0){
+            report.push(Severity::Warning, "settings", None,
+                format!("Technical efficiency {} of hangar {} is not above 0 and at most 1", efficiency, hangar.name));
+        }
+    }
+}
+
+fn check_stations(source: &DataSource, stations: &[(u64, StationReadIn)], hangar_names: &[String], report: &mut ValidationReport){
+    let stations_file = source.describe(InputFile::Stations);
+    for hangar in hangar_names{
+        let hangar_stations: Vec<&(u64, StationReadIn)> = stations.iter().filter(|(_, s)| in_hangar(&s.hangar, hangar)).collect();
+        if hangar_stations.is_empty(){
+            report.push(Severity::Error, &stations_file, None, format!("Hangar {} has no stations", hangar));
+        }
+        for (line, station) in hangar_stations{
+            if station.station != station.station.to_ascii_uppercase(){
+                report.push(Severity::Warning, &stations_file, Some(*line),
+                    format!("Station '{}' will be upper-cased to '{}'", station.station, station.station.to_ascii_uppercase()));
+            }
+        }
+    }
+}
+
+fn check_sot(source: &DataSource, sots: &[(u64, SOTReadIn)], hangars: &[HangarRefs], settings: &Settings, report: &mut ValidationReport){
+    let sot_file = source.describe(InputFile::Sot);
+    let stations_file = source.describe(InputFile::Stations);
+    let shift_system = settings.get_shift_system();
+    for hangar in hangars{
+        let mut covered: Vec<String> = vec![];
+        for (line, sot) in sots.iter().filter(|(_, s)| in_hangar(&s.hangar, hangar.name)){
+            for station in split_list(&sot.stations){
+                check_station_reference(&station, &hangar.stations, hangar.name, &sot_file, *line, report);
+                covered.push(station.to_ascii_uppercase());
+            }
+            if let Err(shift) = shift_system.get_share(&split_names(&sot.shifts)){
+                report.push(Severity::Error, &sot_file, Some(*line), format!("SOT {} works unknown shift {}", sot.sot, shift));
+            }
+            if let Some(rule) = sot.dispatch.as_deref().filter(|rule| !rule.trim().is_empty() && Dispatch::parse(rule).is_none()){
+                report.push(Severity::Error, &sot_file, Some(*line), format!("Unknown dispatch rule {} of SOT {}", rule, sot.sot));
+            }
+            let cvat = sot.cvat.unwrap_or(settings.get_capacity_factors(hangar.name).cvat);
+            if cvat <= 0.0{
+                report.push(Severity::Error, &sot_file, Some(*line), format!("SOT {} has a non-positive cvat {}", sot.sot, cvat));
+            }
+            if sot.absence.is_some_and(|absence| !(0.0..1.0).contains(&absence)){
+                report.push(Severity::Error, &sot_file, Some(*line),
+                    format!("Absence {} of SOT {} is not at least 0 and below 1", sot.absence.unwrap_or_default(), sot.sot));
+            }
+            for (factor, value) in [("Technical efficiency", sot.technical_efficiency), ("Shift efficiency", sot.shift_efficiency)]{
+                if value.is_some_and(|value| !(value > 0.0 && value <= 1.0)){
+                    report.push(Severity::Warning, &sot_file, Some(*line),
+                        format!("{} {} of SOT {} is not above 0 and at most 1", factor, value.unwrap_or_default(), sot.sot));
+                }
+            }
+        }
+        for station in &hangar.stations{
+            if !covered.contains(&station.to_ascii_uppercase()){
+                report.push(Severity::Warning, &stations_file, None,
+                    format!("Station {} of hangar {} is not covered by any SOT", station, hangar.name));
+            }
+        }
+    }
+}
+
+fn check_tasks(source: &DataSource, tasks: &[(u64, TaskReadIn)], workers: &[(u64, WorkerReadIn)], hangars: &[HangarRefs], report: &mut ValidationReport){
+    let tasks_file = source.describe(InputFile::Tasks);
+    for hangar in hangars{
+        let hangar_tasks: Vec<(u64, &TaskReadIn)> = tasks.iter()
+            .filter(|(_, t)| in_hangar(&t.hangar, hangar.name))
+            .map(|(line, task)| (*line, task))
+            .collect();
+        for (line, task) in &hangar_tasks{
+            for station in split_list(&task.station){
+                check_station_reference(&station, &hangar.stations, hangar.name, &tasks_file, *line, report);
+            }
+            if let Some(message) = util::check_station_order(&split_list(&task.station), &hangar.stations){
+                report.push(Severity::Error, &tasks_file, Some(*line), message);
+            }
+            check_sot_reference(&task.sot, &hangar.sots, hangar.name, &tasks_file, *line, report);
+            if task.workload < 0.0{
+                report.push(Severity::Error, &tasks_file, Some(*line), format!("Negative workload {}", task.workload));
+            }
+            if let Some(predicted) = task.predicted_workload.filter(|predicted| *predicted < 0.0){
+                report.push(Severity::Error, &tasks_file, Some(*line), format!("Negative predicted workload {}", predicted));
+            }
+            // Anonymous workers of the bc headcount hold no qualifications
+            let required = split_names(&task.qualifications);
+            let qualified = workers.iter()
+                .filter(|(_, w)| in_hangar(&w.hangar, hangar.name) && w.sot == task.sot)
+                .any(|(_, w)| required.iter().all(|q| split_names(&w.qualifications).contains(q)));
+            if !required.is_empty() && !qualified{
+                report.push(Severity::Warning, &tasks_file, Some(*line),
+                    format!("No worker of SOT {} holds {}, the task will not be worked on", task.sot, required.join(" and ")));
+            }
+        }
+        for issue in util::check_precedence(&hangar_tasks){
+            report.push(Severity::Error, &tasks_file, Some(issue.line), issue.message);
+        }
+    }
+}
+
+fn check_tact_plan(source: &DataSource, tact_plan: &[(u64, MSNReadIn)], tasks: &[(u64, TaskReadIn)], several_hangars: bool, hangars: &[HangarRefs], report: &mut ValidationReport){
+    let tactplan_file = source.describe(InputFile::TactPlan);
+    if several_hangars{
+        for (line, msn) in tact_plan.iter().filter(|(_, m)| m.hangar.as_deref().unwrap_or("").is_empty()){
+            report.push(Severity::Error, &tactplan_file, Some(*line),
+                format!("MSN {} has no hangar, but more than one hangar is simulated", msn.msn));
+        }
+    }
+    for hangar in hangars{
+        let mut seen_msns: Vec<(u32, u64)> = vec![];
+        for (line, msn) in tact_plan.iter().filter(|(_, m)| in_hangar(&m.hangar, hangar.name)){
+            if let Some((_, first_line)) = seen_msns.iter().find(|(m, _)| *m == msn.msn){
+                report.push(Severity::Error, &tactplan_file, Some(*line),
+                    format!("MSN {} is already planned in line {}", msn.msn, first_line));
+            }
+            else{
+                seen_msns.push((msn.msn, *line));
+            }
+            if !tasks.iter().any(|(_, t)| in_hangar(&t.hangar, hangar.name) && t.version == msn.version){
+                report.push(Severity::Warning, &tactplan_file, Some(*line),
+                    format!("Version {} of MSN {} has no tasks in hangar {}, its workload will be zero", msn.version, msn.msn, hangar.name));
+            }
+        }
+    }
+}
+
+fn check_exceptions(source: &DataSource, exceptions: &[(u64, ExceptionReadIn)], report: &mut ValidationReport){
+    let exceptions_file = source.describe(InputFile::Exceptions);
+    for (line, exception) in exceptions{
+        match ExceptionKind::parse(&exception.kind){
+            None => report.push(Severity::Error, &exceptions_file, Some(*line),
+                format!("Unknown kind {}, expected holiday, shutdown or reduced", exception.kind)),
+            Some(ExceptionKind::Reduced) => match exception.capacity{
+                Some(capacity) if !(0.0..=1.0).contains(&capacity) => report.push(Severity::Error, &exceptions_file, Some(*line),
+                    format!("Capacity {} of a reduced day is not between 0 and 1", capacity)),
+                None => report.push(Severity::Warning, &exceptions_file, Some(*line),
+                    String::from("Reduced day without capacity, half of the day is worked")),
+                _ => {}
+            },
+            Some(_) => {}
+        }
+        if exception.end_date.is_some_and(|end| end < exception.date){
+            report.push(Severity::Error, &exceptions_file, Some(*line), format!("End date is before date {}", exception.date));
+        }
+    }
+}
+
+fn check_workers(source: &DataSource, workers: &[(u64, WorkerReadIn)], hangars: &[HangarRefs], settings: &Settings, report: &mut ValidationReport){
+    let workers_file = source.describe(InputFile::Workers);
+    let shift_system = settings.get_shift_system();
+    for hangar in hangars{
+        let mut seen_workers: Vec<(&String, u64)> = vec![];
+        for (line, worker) in workers.iter().filter(|(_, w)| in_hangar(&w.hangar, hangar.name)){
+            if let Some((_, first_line)) = seen_workers.iter().find(|(id, _)| **id == worker.id){
+                report.push(Severity::Error, &workers_file, Some(*line),
+                    format!("Worker {} is already listed in line {}", worker.id, first_line));
+            }
+            else{
+                seen_workers.push((&worker.id, *line));
+            }
+            if !hangar.sots.contains(&worker.sot){
+                report.push(Severity::Error, &workers_file, Some(*line),
+                    format!("SOT {} of worker {} is not listed for hangar {} in sot.csv", worker.sot, worker.id, hangar.name));
+            }
+            if let Err(shift) = shift_system.get_share(&split_names(&worker.shift)){
+                report.push(Severity::Error, &workers_file, Some(*line), format!("Worker {} works unknown shift {}", worker.id, shift));
+            }
+            if worker.efficiency.is_some_and(|efficiency| efficiency <= 0.0){
+                report.push(Severity::Error, &workers_file, Some(*line),
+                    format!("Worker {} has a non-positive efficiency {}", worker.id, worker.efficiency.unwrap_or_default()));
+            }
+        }
+    }
+}
+
+fn check_staffing(source: &DataSource, staffing: &[(u64, StaffingReadIn)], workers: &[(u64, WorkerReadIn)], hangars: &[HangarRefs], settings: &Settings, report: &mut ValidationReport){
+    let staffing_file = source.describe(InputFile::Staffing);
+    for (line, change) in staffing{
+        match (change.from_tact, change.from_date){
+            (None, None) => report.push(Severity::Error, &staffing_file, Some(*line), String::from("A staffing change needs from_tact or from_date")),
+            (None, Some(_)) if settings.get_calendar().is_none() => report.push(Severity::Error, &staffing_file, Some(*line),
+                String::from("from_date needs a calendar start date")),
+            _ => {}
+        }
+        if change.cvat.is_some_and(|cvat| cvat <= 0.0){
+            report.push(Severity::Error, &staffing_file, Some(*line),
+                format!("SOT {} has a non-positive cvat {}", change.sot, change.cvat.unwrap_or_default()));
+        }
+    }
+    for hangar in hangars{
+        for (line, change) in staffing.iter().filter(|(_, c)| in_hangar(&c.hangar, hangar.name)){
+            if check_sot_reference(&change.sot, &hangar.sots, hangar.name, &staffing_file, *line, report)
+                && workers.iter().any(|(_, w)| in_hangar(&w.hangar, hangar.name) && w.sot == change.sot){
+                report.push(Severity::Error, &staffing_file, Some(*line),
+                    format!("SOT {} has workers in workers.csv, plan its staff there", change.sot));
+            }
+        }
+    }
+}
+
+fn check_holidays(source: &DataSource, holidays: &[(u64, HolidayReadIn)], hangars: &[HangarRefs], settings: &Settings, report: &mut ValidationReport){
+    let holidays_file = source.describe(InputFile::Holidays);
+    if !holidays.is_empty() && settings.get_calendar().is_none(){
+        report.push(Severity::Error, &holidays_file, None,
+            String::from("Holidays are planned by date and need a calendar start date"));
+    }
+    for (line, holiday) in holidays{
+        if holiday.end_date.is_some_and(|end| end < holiday.start_date){
+            report.push(Severity::Error, &holidays_file, Some(*line), format!("End date is before start date {}", holiday.start_date));
+        }
+    }
+    for hangar in hangars{
+        for (line, holiday) in holidays.iter().filter(|(_, h)| in_hangar(&h.hangar, hangar.name)){
+            let worker = holiday.worker.as_ref().filter(|worker| !worker.is_empty());
+            let sot = holiday.sot.as_ref().filter(|sot| !sot.is_empty());
+            match (worker, sot){
+                (Some(worker), _) if !hangar.workers.contains(worker) => report.push(Severity::Error, &holidays_file, Some(*line),
+                    format!("Worker {} is not a worker of hangar {}", worker, hangar.name)),
+                (None, Some(sot)) => {
+                    check_sot_reference(sot, &hangar.sots, hangar.name, &holidays_file, *line, report);
+                }
+                (None, None) => report.push(Severity::Error, &holidays_file, Some(*line), String::from("A holiday needs a worker or a sot")),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn check_sick_leave(source: &DataSource, sick_leave: &[(u64, SickLeaveReadIn)], hangars: &[HangarRefs], report: &mut ValidationReport){
+    let sick_leave_file = source.describe(InputFile::SickLeave);
+    for (line, rate) in sick_leave{
+        if !(0.0..=1.0).contains(&rate.rate){
+            report.push(Severity::Error, &sick_leave_file, Some(*line), format!("Sick-leave rate {} is not between 0 and 1", rate.rate));
+        }
+        if rate.month.is_some_and(|month| !(1..=12).contains(&month)){
+            report.push(Severity::Error, &sick_leave_file, Some(*line), format!("Month {} is not between 1 and 12", rate.month.unwrap_or_default()));
+        }
+    }
+    for hangar in hangars{
+        for (line, rate) in sick_leave.iter().filter(|(_, r)| in_hangar(&r.hangar, hangar.name)){
+            if let Some(sot) = rate.sot.as_ref().filter(|sot| !sot.is_empty()){
+                check_sot_reference(sot, &hangar.sots, hangar.name, &sick_leave_file, *line, report);
+            }
+        }
+    }
+}
+
+fn check_learning(source: &DataSource, learning: &[(u64, LearningReadIn)], tact_plan: &[(u64, MSNReadIn)], hangars: &[HangarRefs], report: &mut ValidationReport){
+    let learning_file = source.describe(InputFile::Learning);
+    for (line, curve) in learning{
+        if !(curve.rate > 0.0 && curve.rate <= 1.0){
+            report.push(Severity::Error, &learning_file, Some(*line), format!("Learning rate {} is not above 0 and at most 1", curve.rate));
+        }
+        if curve.floor.is_some_and(|floor| !(floor > 0.0 && floor <= 1.0)){
+            report.push(Severity::Error, &learning_file, Some(*line), format!("Floor {} is not above 0 and at most 1", curve.floor.unwrap_or_default()));
+        }
+    }
+    for hangar in hangars{
+        for (line, curve) in learning.iter().filter(|(_, c)| in_hangar(&c.hangar, hangar.name)){
+            if let Some(sot) = curve.sot.as_ref().filter(|sot| !sot.is_empty()){
+                check_sot_reference(sot, &hangar.sots, hangar.name, &learning_file, *line, report);
+            }
+            if let Some(version) = curve.version.as_ref().filter(|version| !version.is_empty()){
+                if !tact_plan.iter().any(|(_, m)| in_hangar(&m.hangar, hangar.name) && &m.version == version){
+                    report.push(Severity::Warning, &learning_file, Some(*line),
+                        format!("Version {} is not planned for hangar {} in tactplan.csv", version, hangar.name));
+                }
+            }
+        }
+    }
+}
+
+fn check_overtime(source: &DataSource, overtime: &[(u64, OvertimeReadIn)], hangars: &[HangarRefs], report: &mut ValidationReport){
+    let overtime_file = source.describe(InputFile::Overtime);
+    for (line, policy) in overtime{
+        let negative = [Some(policy.max_per_tact), policy.max_per_week, policy.threshold, policy.cost].into_iter().flatten().any(|value| value < 0.0);
+        if negative{
+            report.push(Severity::Error, &overtime_file, Some(*line), format!("Overtime policy of SOT {} has a negative value", policy.sot));
+        }
+        if policy.max_per_week.is_some_and(|max_per_week| max_per_week < policy.max_per_tact){
+            report.push(Severity::Warning, &overtime_file, Some(*line),
+                format!("Weekly overtime limit of SOT {} is below its limit per tact", policy.sot));
+        }
+    }
+    for hangar in hangars{
+        let mut seen_overtime: Vec<(&String, u64)> = vec![];
+        for (line, policy) in overtime.iter().filter(|(_, p)| in_hangar(&p.hangar, hangar.name)){
+            check_sot_reference(&policy.sot, &hangar.sots, hangar.name, &overtime_file, *line, report);
+            match seen_overtime.iter().find(|(sot, _)| **sot == policy.sot){
+                Some((_, first_line)) => report.push(Severity::Warning, &overtime_file, Some(*line),
+                    format!("SOT {} already has an overtime policy in line {}, this one replaces it", policy.sot, first_line)),
+                None => seen_overtime.push((&policy.sot, *line)),
+            }
+        }
+    }
+}
+
+fn check_downtime(source: &DataSource, downtime: &[(u64, DowntimeReadIn)], hangars: &[HangarRefs], settings: &Settings, report: &mut ValidationReport){
+    let downtime_file = source.describe(InputFile::Downtime);
+    for (line, event) in downtime{
+        match (event.start_tact, event.start_date){
+            (None, None) => report.push(Severity::Error, &downtime_file, Some(*line), String::from("A downtime needs start_tact or start_date")),
+            (None, Some(_)) if settings.get_calendar().is_none() => report.push(Severity::Error, &downtime_file, Some(*line),
+                String::from("start_date needs a calendar start date")),
+            _ => {}
+        }
+        if !event.duration.is_finite(){
+            report.push(Severity::Error, &downtime_file, Some(*line), format!("Downtime has a duration {} that is not a finite number of hours", event.duration));
+        }
+        else if event.duration <= 0.0{
+            report.push(Severity::Error, &downtime_file, Some(*line), format!("Downtime has a non-positive duration {}", event.duration));
+        }
+    }
+    for hangar in hangars{
+        for (line, event) in downtime.iter().filter(|(_, d)| in_hangar(&d.hangar, hangar.name)){
+            if let Some(station) = event.station.as_ref().filter(|station| !station.is_empty()){
+                check_station_reference(station, &hangar.stations, hangar.name, &downtime_file, *line, report);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::datasource::fixture;
+    use crate::unfinished::ExitPolicy;
+
+    #[test]
+    fn fixture_is_valid(){
+        let report = validate(&fixture(), &[], &Settings::new());
+        assert!(!report.has_errors(), "{}", report);
+    }
+
+    #[test]
+    fn multi_station_task_out_of_line_order(){
+        let source = fixture().with_data(InputFile::Tasks, "station,sot,version,workload\nS1;S2,MECH,A,10\nS3;S1,MECH,A,10\nS1;S3,MECH,A,10\n");
+        let messages: Vec<String> = validate(&source, &[], &Settings::new()).get_issues().iter().map(|i| i.to_string()).collect();
+        assert!(!messages.iter().any(|m| m.contains("tasks.csv:2:")), "{:#?}", messages);
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:3: Stations S3;S1 are not consecutive")), "{:#?}", messages);
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:4: Stations S1;S3 are not consecutive")), "{:#?}", messages);
+    }
+
+    #[test]
+    fn msn_needs_a_hangar_with_several_hangars(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station,hangar\nS1,8\nS1,9\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,2,0.8,S1\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload\nS1,MECH,A,10\n")
+            .with_data(InputFile::TactPlan, "msn,version,hangar\n1,A,8\n2,A,\n");
+        let messages: Vec<String> = validate(&source, &[], &Settings::new()).get_issues().iter().map(|i| i.to_string()).collect();
+        assert!(messages.iter().any(|m| m.contains("tactplan.csv:3: MSN 2 has no hangar")), "{:#?}", messages);
+        assert!(!validate(&source, &[String::from("8")], &Settings::new()).has_errors());
+    }
+
+    #[test]
+    fn collects_every_issue(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\nS2\nS3\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations,dispatch\nMECH,4,0.8,s1;S2,random\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload,qualifications\nS1,MECH,A,10,\nS9,MECH,A,5,\nS2,mech,A,5,\nS2,MECH,A,-1,\nS2,MECH,A,abc,\nS1,MECH,A,2,NDT\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n1,A\n2,B\n")
+            .with_data(InputFile::Exceptions, "date,kind,capacity\n2026-01-01,holiday,\n2026-01-02,vacation,\n")
+            .with_data(InputFile::Workers, "id,sot,efficiency\nW1,MECH,1.1\nW1,PAINT,0\n")
+            .with_data(InputFile::SickLeave, "sot,month,rate\nMECH,13,0.1\n,,1.5\n")
+            .with_data(InputFile::Holidays, "worker,sot,start_date,end_date\nW1,,2026-08-03,2026-08-14\nW9,,2026-08-03,\n,,2026-08-03,\n");
+        let mut settings = Settings::new();
+        settings.set_exit_policy(ExitPolicy::Rework);
+        let report = validate(&source, &[], &settings);
+        let messages: Vec<String> = report.get_issues().iter().map(|i| i.to_string()).collect();
+
+        assert!(report.has_errors());
+        assert!(messages.iter().any(|m| m.contains("Unfinished work capacity")));
+        assert!(messages.iter().any(|m| m.contains("'s1' does not match")), "{:#?}", messages);
+        assert!(messages.iter().any(|m| m.contains("S3 of hangar 1 is not covered")));
+        assert!(messages.iter().any(|m| m.contains("sot.csv:2: Unknown dispatch rule random of SOT MECH")));
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:3: Station S9")));
+        assert!(messages.iter().any(|m| m.contains("'mech' differs in case")));
+        assert!(messages.iter().any(|m| m.contains("Negative workload")));
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:7: No worker of SOT MECH holds NDT")));
+        assert!(messages.iter().any(|m| m.contains("column 'workload'")));
+        assert!(messages.iter().any(|m| m.contains("MSN 1 is already planned in line 2")));
+        assert!(messages.iter().any(|m| m.contains("Version B of MSN 2 has no tasks")));
+        assert!(messages.iter().any(|m| m.contains("exceptions.csv:3: Unknown kind vacation")));
+        assert!(messages.iter().any(|m| m.contains("Worker W1 is already listed in line 2")));
+        assert!(messages.iter().any(|m| m.contains("SOT PAINT of worker W1")));
+        assert!(messages.iter().any(|m| m.contains("non-positive efficiency 0")));
+        assert!(messages.iter().any(|m| m.contains("sickleave.csv:2: Month 13")));
+        assert!(messages.iter().any(|m| m.contains("sickleave.csv:3: Sick-leave rate 1.5")));
+        assert!(messages.iter().any(|m| m.contains("holidays.csv: Holidays are planned by date and need a calendar start date")), "{:#?}", messages);
+        assert!(messages.iter().any(|m| m.contains("holidays.csv:3: Worker W9 is not a worker of hangar 1")));
+        assert!(messages.iter().any(|m| m.contains("holidays.csv:4: A holiday needs a worker or a sot")));
+        assert!(!messages.iter().any(|m| m.contains("holidays.csv:2")));
+    }
+
+    #[test]
+    fn sot_references_are_checked_in_every_file(){
+        let source = fixture()
+            .with_data(InputFile::Staffing, "sot,from_tact,bc\nPAINT,1,2\n")
+            .with_data(InputFile::Holidays, "sot,start_date\nmech,2026-08-03\n")
+            .with_data(InputFile::SickLeave, "sot,rate\nPAINT,0.1\n")
+            .with_data(InputFile::Overtime, "sot,max_per_tact\nPAINT,2\n")
+            .with_data(InputFile::Learning, "sot,version,rate\nPAINT,,0.9\n");
+        let report = validate(&source, &[], &Settings::new());
+        let messages: Vec<String> = report.get_issues().iter().map(|i| i.to_string()).collect();
+
+        for file in ["staffing.csv:2", "sickleave.csv:2", "overtime.csv:2", "learning.csv:2"]{
+            assert!(messages.iter().any(|m| m.contains(&format!("{}: SOT PAINT is not listed for hangar 1 in sot.csv", file))), "{:#?}", messages);
+        }
+        assert!(messages.iter().any(|m| m.contains("holidays.csv:2: SOT 'mech' differs in case from SOT 'MECH' in sot.csv")));
+    }
+
+    #[test]
+    fn downtime_needs_a_finite_duration(){
+        let source = fixture().with_data(InputFile::Downtime, "station,start_tact,duration\nS1,0,inf\nS1,0,0\nS1,0,4\n");
+        let report = validate(&source, &[], &Settings::new());
+        let messages: Vec<String> = report.get_issues().iter().map(|i| i.to_string()).collect();
+
+        assert!(messages.iter().any(|m| m.contains("downtime.csv:2: Downtime has a duration inf that is not a finite number of hours")), "{:#?}", messages);
+        assert!(messages.iter().any(|m| m.contains("downtime.csv:3: Downtime has a non-positive duration 0")));
+        assert!(!messages.iter().any(|m| m.contains("downtime.csv:4")));
+    }
+
+    #[test]
+    fn precedence_issues(){
+        let source = DataSource::memory()
+            .with_data(InputFile::Stations, "station\nS1\n")
+            .with_data(InputFile::Sot, "sot,bc,cvat,stations\nMECH,4,0.8,S1\n")
+            .with_data(InputFile::Tasks, "station,sot,version,workload,id,predecessors\nS1,MECH,A,10,T1,\nS1,MECH,A,10,T1,\nS1,MECH,A,10,T2,T9\nS1,MECH,A,10,T3,T3\nS1,MECH,A,10,T4,T5\nS1,MECH,A,10,T5,T4\nS1,MECH,A,10,,T5\nS1,MECH,B,10,T6,T1\n")
+            .with_data(InputFile::TactPlan, "msn,version\n1,A\n");
+        let report = validate(&source, &[], &Settings::new());
+        let messages: Vec<String> = report.get_issues().iter().map(|i| i.to_string()).collect();
+
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:3: Task id T1 of version A is already used in line 2")), "{:#?}", messages);
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:4: Predecessor T9 is not a task of version A")));
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:5: Task T3 is its own predecessor")));
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:6: Task of SOT MECH waits for a cycle")));
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:8: Task of SOT MECH waits for a cycle")));
+        assert!(messages.iter().any(|m| m.contains("tasks.csv:9: Predecessor T1 is not a task of version B")));
+        assert!(!messages.iter().any(|m| m.contains("tasks.csv:2:")));
+        assert!(!messages.iter().any(|m| m.contains("tasks.csv:5: Task of SOT")));
+    }
+}
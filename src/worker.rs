@@ -0,0 +1,107 @@
+#[derive(Debug, Clone)]
+pub struct Worker{
+    id: String,
+    // Home SOT, the worker only works on its tasks
+    sot: String,
+    // Productive hours per present hour, relative to the SOT's cvat
+    efficiency: f64,
+    shift: Option<String>,
+    // Part of the line's hours the worker is present, the home SOT's share if None
+    shift_share: Option<f64>,
+    qualifications: Vec<String>,
+    // Left the SOT through the staffing plan, kept for the hours of the tacts before
+    employed: bool,
+    // Sick in the current tact
+    absent: bool,
+    // Part of the current tact the worker is on holiday
+    holiday_share: f64,
+    // Hours worked per tact
+    hours: Vec<f64>,
+}
+
+impl Worker{
+    pub fn new(id: &str, sot: &str, efficiency: f64) -> Self{
+        Self {
+            id: String::from(id),
+            sot: String::from(sot),
+            efficiency,
+            shift: None,
+            shift_share: None,
+            qualifications: vec![],
+            employed: true,
+            absent: false,
+            holiday_share: 0.0,
+            hours: vec![],
+        }
+    }
+
+    pub fn set_shift(&mut self, shift: &str, shift_share: f64){
+        self.shift = Some(String::from(shift));
+        self.shift_share = Some(shift_share);
+    }
+
+    pub fn set_qualifications(&mut self, qualifications: Vec<String>){
+        self.qualifications = qualifications;
+    }
+
+    pub fn get_id(&self) -> &str{
+        &self.id
+    }
+
+    pub fn get_sot(&self) -> &str{
+        &self.sot
+    }
+
+    pub fn get_efficiency(&self) -> f64{
+        self.efficiency
+    }
+
+    pub fn get_shift(&self) -> Option<&String>{
+        self.shift.as_ref()
+    }
+
+    pub fn get_shift_share(&self) -> Option<f64>{
+        self.shift_share
+    }
+
+    pub fn get_qualifications(&self) -> &Vec<String>{
+        &self.qualifications
+    }
+
+    /// Whether the worker holds every qualification a task requires.
+    pub fn is_qualified(&self, required: &[String]) -> bool{
+        required.iter().all(|qualification| self.qualifications.contains(qualification))
+    }
+
+    pub fn set_employed(&mut self, employed: bool){
+        self.employed = employed;
+    }
+
+    pub fn is_employed(&self) -> bool{
+        self.employed
+    }
+
+    pub fn set_absent(&mut self, absent: bool){
+        self.absent = absent;
+    }
+
+    pub fn is_absent(&self) -> bool{
+        self.absent
+    }
+
+    pub fn set_holiday_share(&mut self, holiday_share: f64){
+        self.holiday_share = holiday_share;
+    }
+
+    pub fn get_holiday_share(&self) -> f64{
+        self.holiday_share
+    }
+
+    pub fn add_hours(&mut self, hours: f64){
+        self.hours.push(hours);
+    }
+
+    pub fn get_hours(&self) -> &Vec<f64>{
+        &self.hours
+    }
+}